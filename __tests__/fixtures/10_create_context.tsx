@@ -0,0 +1,7 @@
+export const ThemeContext = React.createContext('light');
+const UserContext = createContext(null);
+
+export function Profile() {
+  const user = useContext(UserContext);
+  return user.name;
+}
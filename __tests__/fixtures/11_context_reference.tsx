@@ -0,0 +1,6 @@
+export function Settings() {
+  const user = useContext(UserContext);
+  const theme = use(Contexts.Theme);
+  const locale = useContext(getLocaleContext());
+  return [user, theme, locale];
+}
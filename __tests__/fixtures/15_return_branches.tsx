@@ -0,0 +1,10 @@
+export function Orders({ userId }) {
+  const { data, loading, error } = useOrders(userId);
+  if (loading) return 'Loading';
+  if (error) {
+    return error.message;
+  } else if (data.length === 0 && !error && userId !== undefined) {
+    return 'No orders';
+  }
+  return data;
+}
@@ -0,0 +1,4 @@
+export function Dashboard() {
+  const stats = useStats();
+  return stats.total;
+}
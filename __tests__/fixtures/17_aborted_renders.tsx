@@ -0,0 +1,5 @@
+export function Invoice({ id }) {
+  const invoice = useSuspenseQuery(id);
+  if (!invoice) return null;
+  return invoice.total;
+}
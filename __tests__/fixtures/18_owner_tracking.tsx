@@ -0,0 +1,7 @@
+export function TodoList({ todos }) {
+  const filter = useFilter();
+  const visible = todos.filter((todo) => {
+    return filter(todo);
+  });
+  return visible.map((todo) => todo.title);
+}
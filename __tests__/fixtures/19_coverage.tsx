@@ -0,0 +1,10 @@
+export function Counter() {
+  const [count, setCount] = useCounter(0);
+  const theme = useTheme();
+  return count;
+}
+
+export function Footer() {
+  const links = useLinks();
+  return links;
+}
@@ -0,0 +1,12 @@
+import { memo, useMemo } from 'react';
+
+const Row = memo(function Row({ items }) {
+  return items.length;
+});
+
+export function List({ items = [], onSelect }) {
+  const sorted = useSorted(items);
+  const total = useMemo(() => sorted.length, [sorted, { order: 'asc' }]);
+  const row = <Row items={sorted} total={total} onSelect={() => onSelect(sorted)} />;
+  return <ThemeContext.Provider value={{ dark: true }}>{row}</ThemeContext.Provider>;
+}
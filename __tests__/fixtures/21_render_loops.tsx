@@ -0,0 +1,24 @@
+import { useEffect, useState } from 'react';
+
+export function Clock({ start }) {
+  const [time, setTime] = useState(start);
+  const [ticks, setTicks] = useState(0);
+  const label = useLabel(time);
+
+  setTicks(ticks + 1);
+  if (time < start) {
+    setTime(start);
+  }
+
+  useEffect(() => {
+    setTime(Date.now());
+    const timer = setInterval(() => setTicks(0), 1000);
+    return () => clearInterval(timer);
+  });
+
+  useEffect(() => {
+    setTicks(0);
+  }, [start]);
+
+  return label;
+}
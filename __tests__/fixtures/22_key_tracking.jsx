@@ -0,0 +1,6 @@
+export function TodoList({ todos }) {
+  const filter = useFilter();
+  const rows = todos.filter(filter).map((todo, index) => <li key={todo.id + index}>{todo.title}</li>);
+  const tags = todos.map((todo) => <Tag key={todo.tag.id} tag={todo.tag} />);
+  return rows.concat(tags);
+}
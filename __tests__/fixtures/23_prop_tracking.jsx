@@ -0,0 +1,4 @@
+export function Table({ rows, onSelect }) {
+  const columns = useColumns();
+  return <Grid rows={rows} columns={columns} size="small" dense={true} onRowClick={(row) => onSelect(row.id)} />;
+}
@@ -0,0 +1,13 @@
+export function useCart() {
+  const items = useSelector(selectItems);
+  const total = useTotal(items);
+  if (items.length === 0) {
+    return null;
+  }
+  return { items, total };
+}
+
+export function CartPage() {
+  const cart = useCart();
+  return cart;
+}
@@ -0,0 +1,11 @@
+export function List({ items }) {
+  const selected = useSelection();
+  const Empty = () => null;
+
+  function Item({ item }) {
+    const theme = useTheme();
+    return theme.render(item, selected);
+  }
+
+  return items.map((item) => Item({ item }));
+}
@@ -0,0 +1,9 @@
+import { useReducer, useState } from 'react';
+
+export function Search({ items, query }) {
+  const [index] = useState(buildIndex(items));
+  const [lazyIndex] = useState(() => buildIndex(items));
+  const [state, dispatch] = useReducer(reducer, new Map(items));
+  const results = useSearch(index, query);
+  return results;
+}
@@ -0,0 +1,7 @@
+import { useContext } from 'react';
+
+export function Profile({ userId }) {
+  const theme = useContext(ThemeContext);
+  const user = useUser(userId);
+  return user.name;
+}
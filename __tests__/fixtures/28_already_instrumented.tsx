@@ -0,0 +1,26 @@
+import { useJitterScope } from "react-jitter/runtime";
+export function Header() {
+    const h = useJitterScope({
+        name: "Header",
+        id: "2d1b5c0e",
+        file: "28_already_instrumented.tsx",
+        line: 2,
+        offset: 7
+    });
+    const user = (h.s("5e0f4a1b"), h.e(useUser(), {
+        id: "5e0f4a1b",
+        file: "28_already_instrumented.tsx",
+        hook: "useUser",
+        line: 10,
+        offset: 19
+    }));
+    return h.re(user.name, {
+        index: 0,
+        line: 17
+    });
+}
+
+export function Footer() {
+    const year = useYear();
+    return year;
+}
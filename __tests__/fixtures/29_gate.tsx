@@ -0,0 +1,7 @@
+export function Profile({ userId }) {
+  const user = useUser(userId);
+  if (!user) {
+    return null;
+  }
+  return user.name;
+}
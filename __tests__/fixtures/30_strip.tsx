@@ -0,0 +1,62 @@
+import { useState } from 'react';
+import { useJitterScope, disabledScope, reactJitter, tagContext } from 'react-jitter/runtime';
+
+reactJitter({ enabled: true });
+
+const __reactJitterInstrumented = true;
+const _jitterHook0 = Object.freeze({
+  id: '5e0f4a1b',
+  file: 'Header.tsx',
+  hook: 'useUser',
+  line: 12,
+  offset: 19,
+});
+const _jitterEnabled = !!globalThis.__REACT_JITTER__;
+
+export const UserContext = tagContext(createContext(null), { id: 'c1', name: 'UserContext' });
+
+export function Header() {
+  const h = _jitterEnabled ? useJitterScope({ id: 'header' }) : disabledScope;
+  const user = _jitterEnabled ? (h.s('5e0f4a1b'), h.e(useUser(), _jitterHook0, useUser)) : useUser();
+  return h.re(user.name, { index: 0, line: 14 });
+}
+
+export function Counter() {
+  const scope = useJitterScope({ name: 'Counter', id: 'counter' });
+  try {
+    const [count, setCount] = useState(0);
+    return scope.re(count);
+  } catch (error) {
+    scope.a(error);
+    throw error;
+  } finally {
+    scope.f();
+  }
+}
+
+export function Gauge() {
+  const scope = useJitterScope({ name: 'Gauge', id: 'gauge' });
+  try {
+    return scope.re(readGauge());
+  } catch (error) {
+    scope.a(error);
+    reportError(error);
+    throw error;
+  } finally {
+    scope.f();
+  }
+}
+
+export function parseLimit(text) {
+  switch (typeof text) {
+    case 'string':
+      try {
+        const limit = Number(text);
+        return limit;
+      } catch (error) {
+        throw error;
+      }
+    default:
+      return 0;
+  }
+}
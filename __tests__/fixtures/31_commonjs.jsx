@@ -0,0 +1,9 @@
+'use strict';
+const { useUser } = require('./user');
+
+function Profile({ userId }) {
+  const user = useUser(userId);
+  return user.name;
+}
+
+module.exports = { Profile };
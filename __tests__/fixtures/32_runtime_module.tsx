@@ -0,0 +1,9 @@
+import { useQuery } from '@tanstack/react-query';
+import { reactJitter } from '@acme/jitter';
+
+reactJitter({ enabled: true });
+
+export function OrderCount({ customerId }) {
+  const { data } = useQuery({ queryKey: ['orders', customerId] });
+  return data ? data.length : 0;
+}
@@ -0,0 +1,11 @@
+import { createContext, useContext } from 'react';
+import { useQuery } from '@tanstack/react-query';
+
+export const ThemeContext = createContext('light');
+
+export function Dashboard({ userId }) {
+  const theme = useContext(ThemeContext);
+  const user = useQuery({ queryKey: ['user', userId] });
+  const orders = useQuery({ queryKey: ['orders', userId] });
+  return `${theme}: ${user.data} (${orders.data})`;
+}
@@ -0,0 +1,6 @@
+import { useCart } from './cart';
+
+export function CartBadge() {
+  const cart = useCart();
+  return cart.items.length;
+}
@@ -0,0 +1,19 @@
+import { useEffect } from 'react';
+
+export function Clock({ items, onTick }) {
+  const visible = useVisibleItems(items.filter((item) => {
+    return item.enabled;
+  }));
+
+  useEffect(() => {
+    const timer = setInterval(onTick, 1000);
+    return () => clearInterval(timer);
+  });
+
+  if (visible.length === 0) {
+    return null;
+  }
+  return visible.map(function (item) {
+    return item.label;
+  });
+}
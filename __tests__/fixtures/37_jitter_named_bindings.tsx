@@ -0,0 +1,6 @@
+const _jitterThreshold = 5;
+
+export function Meter({ value }) {
+  const level = useLevel(value, _jitterThreshold);
+  return level;
+}
@@ -0,0 +1,10 @@
+import { useJitterScope } from 'react-jitter/runtime';
+
+const _jitterThreshold = 5;
+const _jitterEnabled = process.env.NODE_ENV !== 'test';
+
+export function Meter({ value }) {
+  const h = useJitterScope({ name: 'Meter', id: 'meter' });
+  const level = _jitterEnabled ? useLevel(value, _jitterThreshold) : 0;
+  return h.re(level);
+}
@@ -0,0 +1,12 @@
+export const Badge = ({ label }) => <span className="badge">{label}</span>;
+
+export const Card = ({ title, children }) => {
+  return (
+    <section>
+      <h2>{title}</h2>
+      {children}
+    </section>
+  );
+};
+
+export const Divider = () => null;
@@ -0,0 +1,27 @@
+import { useMemo } from 'react';
+
+export function Leaderboard({ players, onSelect }) {
+  const sorted = useMemo(() => {
+    const Compare = (a, b) => b.score - a.score;
+    return [...players].sort(Compare);
+  }, [players]);
+
+  const Row = ({ player }) => {
+    const rank = useRank(player);
+    return rank.render(player);
+  };
+
+  const Empty = () => null;
+
+  function handleSelect(player) {
+    const Format = function (p) {
+      return p.name;
+    };
+    onSelect(Format(player));
+  }
+
+  if (sorted.length === 0) {
+    return Empty();
+  }
+  return sorted.map((player) => Row({ player, onSelect: handleSelect }));
+}
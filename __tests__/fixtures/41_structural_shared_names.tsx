@@ -0,0 +1,23 @@
+import { createContext } from 'react';
+
+export function List({ rows }) {
+  const SelectionContext = createContext(null);
+
+  function Item({ row }) {
+    const label = useLabel(row);
+    return label;
+  }
+
+  return rows.map((row) => Item({ row, context: SelectionContext }));
+}
+
+export function Table({ rows }) {
+  const SelectionContext = createContext(null);
+
+  function Item({ row }) {
+    const label = useLabel(row);
+    return label;
+  }
+
+  return rows.map((row) => Item({ row, context: SelectionContext }));
+}
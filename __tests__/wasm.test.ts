@@ -1,4 +1,4 @@
-import { expect, test } from 'vitest';
+import { describe, expect, test } from 'vitest';
 import { transform } from '@swc/core';
 import type { ParserConfig } from '@swc/core';
 import path from 'node:path';
@@ -24,6 +24,7 @@ const transformCode = async (
   code: string,
   options = {},
   filename = 'test.jsx',
+  sourceMaps = false,
 ) => {
   const ext = path.extname(filename);
   const isTypescript = ext === '.ts' || ext === '.tsx';
@@ -37,6 +38,7 @@ const transformCode = async (
       experimental: { plugins: [[pluginPath, options]] },
     },
     filename,
+    sourceMaps,
   });
 };
 
@@ -48,11 +50,232 @@ const fixtureFiles = fs
   .readdirSync(fixturesDir)
   .filter((f) => f.endsWith('.jsx') || f.endsWith('.tsx'));
 
+const fixtureOptions: Record<string, object> = {
+  '1_default.tsx': { includeArguments: true },
+  '12_argument_capture.tsx': {
+    includeArguments: {
+      maxLength: 24,
+      hooks: ['useSession', 'usePrices'],
+      redact: ['sk_live_\\w+'],
+    },
+  },
+  '13_argument_values.tsx': { includeArguments: { mode: 'value' } },
+  '14_argument_identifiers.tsx': {
+    includeArguments: { mode: 'identifiers-only' },
+  },
+  '35_argument_value_redaction.tsx': {
+    includeArguments: {
+      mode: 'value',
+      maxLength: 24,
+      redact: ['sk_live_\\w+'],
+    },
+  },
+  '16_timing.tsx': { timing: { marks: true } },
+  '17_aborted_renders.tsx': { reportAbortedRenders: true },
+  '18_owner_tracking.tsx': { trackOwners: true },
+  '19_coverage.tsx': { coverage: true },
+  '20_lint.jsx': { lint: true },
+  '21_render_loops.tsx': { detectRenderLoops: true },
+  '22_key_tracking.jsx': { trackKeys: true },
+  '23_prop_tracking.jsx': { trackProps: true },
+  '26_state_initializers.tsx': { countInitializers: true },
+  '27_compact_metadata.tsx': { metadata: 'compact' },
+  '42_compact_manifest_file.tsx': {
+    metadata: 'compact',
+    manifestDir: 'node_modules/.cache/react-jitter',
+  },
+  '29_gate.tsx': { gate: 'globalThis.__REACT_JITTER__' },
+  '30_strip.tsx': { mode: 'strip' },
+  '32_runtime_module.tsx': {
+    runtimeModule: '@acme/jitter',
+    runtimeExport: 'useTraceScope',
+  },
+  '33_structural_ids.tsx': { idStrategy: 'structural' },
+  '41_structural_shared_names.tsx': { idStrategy: 'structural' },
+  '34_id_namespace.tsx': { hashLength: 12, namespace: 'checkout' },
+  '38_strip_user_bindings.tsx': { mode: 'strip' },
+};
+
 for (const file of fixtureFiles) {
   test(`fixture: ${file}`, async () => {
     const input = fs.readFileSync(path.join(fixturesDir, file), 'utf-8');
-    const options = file === '1_default.tsx' ? { includeArguments: true } : {};
+    const options = fixtureOptions[file] ?? {};
     const { code } = await transformCode(input, options, file);
     expect(code.trim()).toMatchSnapshot();
   });
 }
+
+test('strip mode fails on runtime exports it cannot remove', async () => {
+  const input = [
+    "import { getCoverage } from 'react-jitter/runtime';",
+    'console.log(getCoverage());',
+  ].join('\n');
+  await expect(
+    transformCode(input, { mode: 'strip' }, 'report.js'),
+  ).rejects.toThrow(/getCoverage/);
+});
+
+test('scripts require the runtime even with importStyle "esm"', async () => {
+  const input = [
+    'function Profile({ userId }) {',
+    '  const user = useUser(userId);',
+    '  return user.name;',
+    '}',
+  ].join('\n');
+  const { code } = await transform(input, {
+    jsc: {
+      parser: { syntax: 'ecmascript', jsx: true },
+      experimental: { plugins: [[pluginPath, { importStyle: 'esm' }]] },
+    },
+    filename: 'legacy.js',
+    isModule: false,
+  });
+  expect(code).toMatch(/require\(["']react-jitter\/runtime["']\)/);
+  expect(code).not.toContain('import ');
+});
+
+test('instrumented modules are left unchanged by a second run', async () => {
+  const file = '37_jitter_named_bindings.tsx';
+  const input = fs.readFileSync(path.join(fixturesDir, file), 'utf-8');
+  const { code } = await transformCode(input, {}, file);
+  const { code: again } = await transformCode(code, {}, file);
+  expect(again).toBe(code);
+});
+
+describe('structural ids', () => {
+  const file = '33_structural_ids.tsx';
+  const input = fs.readFileSync(path.join(fixturesDir, file), 'utf-8');
+  const edited = `// Dashboard\n\n${input}`;
+
+  // Scope, hook and context ids, in the order they are emitted
+  const emittedIds = async (code: string, idStrategy: string) => {
+    const { code: output } = await transformCode(code, { idStrategy }, file);
+    return [...output.matchAll(/id: "([0-9a-f]+)"/g)].map(
+      (match) => match[1],
+    );
+  };
+
+  test('survive lines added above them', async () => {
+    const ids = await emittedIds(input, 'structural');
+    expect(ids).toHaveLength(5);
+    expect(await emittedIds(edited, 'structural')).toEqual(ids);
+  });
+
+  test('location ids change when lines are added above them', async () => {
+    const ids = await emittedIds(input, 'location');
+    const editedIds = await emittedIds(edited, 'location');
+    expect(editedIds).toHaveLength(ids.length);
+    for (const [index, id] of ids.entries()) {
+      expect(editedIds[index]).not.toBe(id);
+    }
+  });
+});
+
+describe('manifestDir', () => {
+  const file = '42_compact_manifest_file.tsx';
+  const input = fs.readFileSync(path.join(fixturesDir, file), 'utf-8');
+  const manifestDir = 'node_modules/.cache/react-jitter';
+
+  test('writes the compact manifest as JSON', async () => {
+    await transformCode(input, { metadata: 'compact', manifestDir }, file);
+    const manifest: {
+      file: string;
+      scopes: Record<string, object>;
+      hooks: Record<string, { hook: string }>;
+    } = JSON.parse(
+      fs.readFileSync(path.join(manifestDir, `${file}.json`), 'utf-8'),
+    );
+    expect(manifest.file).toBe(file);
+    expect(Object.values(manifest.scopes)).toEqual([
+      { name: 'Profile', line: 3, offset: 7 },
+    ]);
+    const hooks = Object.values(manifest.hooks).map((meta) => meta.hook);
+    expect(hooks.sort()).toEqual(['useContext', 'useUser']);
+  });
+
+  test('shrinks the instrumented output', async () => {
+    const size = async (options: object) =>
+      (await transformCode(input, options, file)).code.length;
+    const full = await size({});
+    const registered = await size({ metadata: 'compact' });
+    const written = await size({ metadata: 'compact', manifestDir });
+    expect(written).toBeLessThan(registered);
+    expect(written).toBeLessThan(full);
+  });
+});
+
+const BASE64 =
+  'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/';
+
+const decodeVlq = (segment: string) => {
+  const values: number[] = [];
+  let value = 0;
+  let shift = 0;
+  for (const char of segment) {
+    const digit = BASE64.indexOf(char);
+    value += (digit & 31) << shift;
+    if (digit & 32) {
+      shift += 5;
+    } else {
+      values.push(value & 1 ? -(value >> 1) : value >> 1);
+      value = 0;
+      shift = 0;
+    }
+  }
+  return values;
+};
+
+// Original (1-based) line of the first occurrence of `needle` in the output
+const originalLine = (code: string, map: string, needle: string) => {
+  const before = code.slice(0, code.indexOf(needle)).split('\n');
+  const generatedLine = before.length - 1;
+  const generatedColumn = before[generatedLine].length;
+
+  let line = 0;
+  let found: number | undefined;
+  JSON.parse(map)
+    .mappings.split(';')
+    .forEach((segments: string, index: number) => {
+      let column = 0;
+      for (const segment of segments.split(',').filter(Boolean)) {
+        const [columnDelta, , lineDelta = 0] = decodeVlq(segment);
+        column += columnDelta;
+        line += lineDelta;
+        if (index === generatedLine && column <= generatedColumn) {
+          found = line + 1;
+        }
+      }
+    });
+  return found;
+};
+
+describe('source maps', () => {
+  const input = [
+    'export function Profile({ userId }) {',
+    '  const theme = useTheme();',
+    '  const user = useUser(userId);',
+    '  return user.name;',
+    '}',
+    '',
+    'export const useLabel = () => useTranslation().label;',
+  ].join('\n');
+
+  test.each([
+    ['const h = useJitterScope(_jitterScope0)', 1],
+    ['try {', 1],
+    ['useTheme()', 2],
+    ['useUser(userId)', 3],
+    ['h.re(user.name', 4],
+    ['const h = useJitterScope(_jitterScope4)', 7],
+    ['useTranslation()', 7],
+  ])('maps `%s` to line %i', async (needle, line) => {
+    const { code, map } = await transformCode(
+      input,
+      { reportAbortedRenders: true },
+      'Profile.jsx',
+      true,
+    );
+    expect(code).toContain(needle);
+    expect(originalLine(code, map ?? '', needle)).toBe(line);
+  });
+});
@@ -18,6 +18,176 @@ declare module 'react-jitter' {
      * By default, all node_modules are excluded.
      */
     exclude?: string[];
+
+    /**
+     * Capture the arguments of wrapped hooks and include them in `onHookChange`.
+     * `true` captures the source text of every argument.
+     * Disabled by default.
+     */
+    includeArguments?: boolean | ArgumentCaptureOptions;
+
+    /**
+     * Measure how long each render takes and report it as `renderDuration`
+     * in `onRender`. With `{ marks: true }`, every render is also recorded
+     * as a User Timing measure that shows up in the browser's performance panel.
+     * Disabled by default.
+     */
+    timing?: boolean | { marks?: boolean };
+
+    /**
+     * Wrap component bodies in `try/catch/finally` so renders that suspend or
+     * throw are reported through `onRenderAbort`. Errors are rethrown unchanged.
+     * Disabled by default.
+     */
+    reportAbortedRenders?: boolean;
+
+    /**
+     * Wrap what components render in a context provider so every render report
+     * includes its owner chain (`owners`) and a `renderCause`.
+     * Adds one provider element per instrumented component. Disabled by default.
+     */
+    trackOwners?: boolean;
+
+    /**
+     * Register every instrumented component and hook when a module loads, so
+     * `getCoverage()` can report the ones that never rendered. Disabled by default.
+     */
+    coverage?: boolean;
+
+    /**
+     * Warn at compile time about references that are recreated on every render:
+     * default parameter literals, inline props to `memo` components declared in
+     * the same file, inline Provider values and literals in dependency arrays.
+     * Findings are also added to the scope metadata as `lint`. Disabled by default.
+     */
+    lint?: boolean;
+
+    /**
+     * Warn about state setters called during render or in effects without a
+     * dependency array, and tag those calls so the runtime can stop render loops
+     * (see `renderLoopThreshold`). Disabled by default.
+     */
+    detectRenderLoops?: boolean;
+
+    /**
+     * Record the `key` of elements returned by `.map` callbacks so renders report
+     * lists whose elements remount (`keyChurn`), and warn about index-based keys.
+     * Disabled by default.
+     */
+    trackKeys?: boolean;
+
+    /**
+     * Compare the non-literal props passed to child component elements between
+     * renders, so renders report props recreated every time (`unstableProps`).
+     * Disabled by default.
+     */
+    trackProps?: boolean;
+
+    /**
+     * Count how often a `useState`/`useReducer` initial value that is not lazy
+     * was computed again after the first render (`discardedInitializers`).
+     * Disabled by default.
+     */
+    countInitializers?: boolean;
+
+    /**
+     * `"compact"` emits only ids in the render path and registers the names,
+     * files and locations once per module with `registerManifest`.
+     * Defaults to `"full"`.
+     */
+    metadata?: 'full' | 'compact';
+
+    /**
+     * Directory, relative to the working directory, that `metadata: "compact"`
+     * writes each module's manifest to as `<dir>/<file>.json`, leaving only ids
+     * in the module. The manifest is registered in the module when omitted, or
+     * when it cannot be written.
+     */
+    manifestDir?: string;
+
+    /**
+     * `"structural"` derives ids from the file, component name, hook name and
+     * call ordinal instead of line and column, so they survive edits elsewhere
+     * in the file. Defaults to `"location"`.
+     */
+    idStrategy?: 'location' | 'structural';
+
+    /**
+     * Number of hex characters in generated ids, between 4 and 64.
+     * Defaults to 8.
+     */
+    hashLength?: number;
+
+    /**
+     * Salt mixed into every id, so separately built apps that share a page,
+     * such as microfrontends, never produce the same ids.
+     */
+    namespace?: string;
+
+    /**
+     * Dotted path, such as `globalThis.__REACT_JITTER__` or
+     * `process.env.REACT_JITTER`, read once per module to switch the
+     * instrumentation on. When it is falsy, hooks are called directly and
+     * scopes are replaced by a pass-through. Always on when omitted.
+     */
+    gate?: string;
+
+    /**
+     * `"strip"` removes `react-jitter/runtime` imports and every instrumentation
+     * call instead of adding them, for production builds of code that uses the
+     * runtime by hand or was instrumented already. Runtime exports that cannot
+     * be removed fail the build. Defaults to `"instrument"`.
+     */
+    mode?: 'instrument' | 'strip';
+
+    /**
+     * How the runtime is imported into instrumented files: `"esm"` adds an
+     * `import`, `"cjs"` a `const { ... } = require(...)`. `"auto"` uses
+     * `require` for scripts and for modules that use `require`, `module.exports`
+     * or `exports` without any `import`/`export`. Scripts always use `require`,
+     * since they cannot contain an `import`. Defaults to `"auto"`.
+     */
+    importStyle?: 'esm' | 'cjs' | 'auto';
+
+    /**
+     * Module the runtime is imported from, such as a package that re-exports
+     * `react-jitter/runtime`. Defaults to `"react-jitter/runtime"`.
+     */
+    runtimeModule?: string;
+
+    /**
+     * Export of `runtimeModule` that instrumented components call to create
+     * their scope. Defaults to `"useJitterScope"`.
+     */
+    runtimeExport?: string;
+  }
+
+  export interface ArgumentCaptureOptions {
+    /**
+     * - `source`: the source text of each argument (default)
+     * - `value`: the live value for identifiers, member expressions and literals,
+     *   the source text for anything else
+     * - `identifiers-only`: the source text for identifiers and member expressions,
+     *   a placeholder such as `<object>` or `<call>` for anything else
+     */
+    mode?: 'source' | 'value' | 'identifiers-only';
+
+    /**
+     * Captured strings longer than this are truncated and end with `…`.
+     */
+    maxLength?: number;
+
+    /**
+     * Only capture arguments for these hooks. All wrapped hooks by default.
+     */
+    hooks?: string[];
+
+    /**
+     * Regular expressions (Rust `regex` syntax) applied at compile time.
+     * Every match in a captured string is replaced with `[redacted]`. In
+     * `value` mode this covers string literals but not live values.
+     */
+    redact?: string[];
   }
 
   /**
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+/// First `length` hex characters of the SHA-256 of `input`.
+fn hash_id(input: &str, length: usize) -> String {
+    use sha2::{Digest, Sha256};
+    let mut id = hex::encode(Sha256::digest(input.as_bytes()));
+    id.truncate(length);
+    id
+}
+
+/// Ids generated in one module, salted with `namespace` and cut to `hashLength` characters.
+pub(crate) struct IdRegistry {
+    hash_length: usize,
+    namespace: Option<String>,
+    /// Ids by the input they were hashed from.
+    ids: HashMap<String, String>,
+    /// Line of the code each id was first generated for.
+    lines: HashMap<String, usize>,
+}
+
+impl IdRegistry {
+    pub fn new(hash_length: usize, namespace: Option<String>) -> Self {
+        Self {
+            hash_length,
+            namespace,
+            ids: HashMap::new(),
+            lines: HashMap::new(),
+        }
+    }
+
+    /// Id for `input`, generated for the code at `line`. Ids are cached by input;
+    /// when a different input already produced the same id, the line of that
+    /// code is returned too, since the runtime would merge their histories.
+    pub fn id(&mut self, input: String, line: usize) -> (String, Option<usize>) {
+        if let Some(id) = self.ids.get(&input) {
+            return (id.clone(), None);
+        }
+
+        let id = match &self.namespace {
+            Some(namespace) => hash_id(&format!("{namespace}:{input}"), self.hash_length),
+            None => hash_id(&input, self.hash_length),
+        };
+        let collision = match self.lines.get(&id) {
+            Some(first_line) => Some(*first_line),
+            None => {
+                self.lines.insert(id.clone(), line);
+                None
+            }
+        };
+        self.ids.insert(input, id.clone());
+        (id, collision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_inputs_sharing_an_id() {
+        let mut ids = IdRegistry::new(4, None);
+        let (first, collision) = ids.id("Profile.tsx:68:7".into(), 68);
+        assert_eq!(collision, None);
+
+        let (second, collision) = ids.id("Profile.tsx:376:7".into(), 376);
+        assert_eq!(second, first);
+        assert_eq!(collision, Some(68));
+    }
+
+    #[test]
+    fn repeated_inputs_are_not_collisions() {
+        let mut ids = IdRegistry::new(4, None);
+        let (first, _) = ids.id("Profile.tsx:68:7".into(), 68);
+        assert_eq!(ids.id("Profile.tsx:68:7".into(), 68), (first, None));
+    }
+
+    #[test]
+    fn namespace_changes_ids() {
+        let mut plain = IdRegistry::new(8, None);
+        let mut namespaced = IdRegistry::new(8, Some("checkout".into()));
+        let (plain_id, _) = plain.id("Profile.tsx:68:7".into(), 68);
+        let (namespaced_id, _) = namespaced.id("Profile.tsx:68:7".into(), 68);
+        assert_ne!(plain_id, namespaced_id);
+        assert_eq!(namespaced_id.len(), 8);
+    }
+}
@@ -1,8 +1,17 @@
+mod ids;
+mod lint;
+mod strip;
+
 use serde::Deserialize;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
 use glob::Pattern;
-use swc_core::common::errors::SourceMapper;
-use swc_core::common::{Loc, Span, Spanned, SyntaxContext, DUMMY_SP};
+use regex::Regex;
+use ids::IdRegistry;
+use lint::LintFinding;
+use swc_core::common::errors::{SourceMapper, HANDLER};
+use swc_core::common::util::take::Take;
+use swc_core::common::{BytePos, Loc, Span, Spanned, SyntaxContext, DUMMY_SP};
 use swc_core::ecma::ast::*;
 use swc_core::ecma::utils::{quote_ident, ExprFactory};
 use swc_core::ecma::visit::{Visit, VisitMut, VisitMutWith, VisitWith};
@@ -15,7 +24,7 @@ use swc_core::plugin::{
 #[serde(untagged)]
 pub enum Config {
     All(bool),
-    WithOptions(Options),
+    WithOptions(Box<Options>),
 }
 
 impl Config {
@@ -27,14 +36,242 @@ impl Config {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Default)]
+#[derive(Clone, Debug, Deserialize)]
+#[allow(non_snake_case)]
 pub struct Options {
     #[serde(default = "default_ignored_hooks")]
     pub ignoreHooks: Vec<String>,
     #[serde(default = "default_exclude_patterns")]
     pub exclude: Vec<String>,
     #[serde(default)]
-    pub includeArguments: bool,
+    pub includeArguments: IncludeArguments,
+    #[serde(default)]
+    pub timing: Timing,
+    #[serde(default)]
+    pub reportAbortedRenders: bool,
+    #[serde(default)]
+    pub trackOwners: bool,
+    #[serde(default)]
+    pub coverage: bool,
+    #[serde(default)]
+    pub lint: bool,
+    #[serde(default)]
+    pub detectRenderLoops: bool,
+    #[serde(default)]
+    pub trackKeys: bool,
+    #[serde(default)]
+    pub trackProps: bool,
+    #[serde(default)]
+    pub countInitializers: bool,
+    #[serde(default)]
+    pub metadata: MetadataMode,
+    /// Directory, relative to the working directory, that compact metadata is written
+    /// to as one JSON manifest per module instead of being registered in the module.
+    #[serde(default)]
+    pub manifestDir: Option<String>,
+    #[serde(default)]
+    pub idStrategy: IdStrategy,
+    /// Number of hex characters in generated ids.
+    #[serde(default = "default_hash_length")]
+    pub hashLength: usize,
+    /// Salt mixed into every id, so separately built apps on one page never share ids.
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub mode: Mode,
+    #[serde(default)]
+    pub importStyle: ImportStyle,
+    /// Module the runtime is imported from, such as a package that re-exports
+    /// `react-jitter/runtime`.
+    #[serde(default = "default_runtime_module")]
+    pub runtimeModule: String,
+    /// Export of `runtimeModule` used in place of `useJitterScope`.
+    #[serde(default = "default_runtime_export")]
+    pub runtimeExport: String,
+    /// Dotted path such as `globalThis.__REACT_JITTER__` that switches the
+    /// instrumentation on at runtime. Always on when omitted.
+    #[serde(default)]
+    pub gate: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Instrument components and hooks.
+    #[default]
+    Instrument,
+    /// Remove runtime imports and instrumentation calls, for production builds.
+    Strip,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStyle {
+    /// `import { useJitterScope } from "react-jitter/runtime"`, except in scripts.
+    Esm,
+    /// `const { useJitterScope } = require("react-jitter/runtime")`.
+    Cjs,
+    /// `require` for scripts and modules that use CommonJS, `import` otherwise.
+    #[default]
+    Auto,
+}
+
+impl Default for Options {
+    /// The options of a plain `true` config. Unlike `{}`, it wraps every hook and
+    /// excludes no files; only settings without a usable zero value get theirs.
+    fn default() -> Self {
+        Self {
+            ignoreHooks: Vec::new(),
+            exclude: Vec::new(),
+            includeArguments: IncludeArguments::default(),
+            timing: Timing::default(),
+            reportAbortedRenders: false,
+            trackOwners: false,
+            coverage: false,
+            lint: false,
+            detectRenderLoops: false,
+            trackKeys: false,
+            trackProps: false,
+            countInitializers: false,
+            metadata: MetadataMode::default(),
+            manifestDir: None,
+            idStrategy: IdStrategy::default(),
+            hashLength: default_hash_length(),
+            namespace: None,
+            mode: Mode::default(),
+            importStyle: ImportStyle::default(),
+            runtimeModule: default_runtime_module(),
+            runtimeExport: default_runtime_export(),
+            gate: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataMode {
+    /// Every scope and hook call carries its name, file and location.
+    #[default]
+    Full,
+    /// Only ids are emitted in the render path; the rest goes to a module manifest.
+    Compact,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IdStrategy {
+    /// Ids hash the file and the line and column of the code they identify.
+    #[default]
+    Location,
+    /// Ids hash the file, the component name, the hook name and the ordinal of
+    /// the call within the component, so edits elsewhere in the file keep them.
+    Structural,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum IncludeArguments {
+    All(bool),
+    WithOptions(ArgumentOptions),
+}
+
+impl Default for IncludeArguments {
+    fn default() -> Self {
+        IncludeArguments::All(false)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Timing {
+    All(bool),
+    WithOptions(TimingOptions),
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Timing::All(false)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Default)]
+pub struct TimingOptions {
+    /// Emit `performance.mark`/`performance.measure` entries for each render.
+    #[serde(default)]
+    pub marks: bool,
+}
+
+impl Timing {
+    fn options(self) -> Option<TimingOptions> {
+        match self {
+            Timing::All(false) => None,
+            Timing::All(true) => Some(TimingOptions::default()),
+            Timing::WithOptions(options) => Some(options),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Default)]
+#[allow(non_snake_case)]
+pub struct ArgumentOptions {
+    #[serde(default)]
+    pub mode: ArgumentMode,
+    /// Longer captured strings are cut and end with an ellipsis.
+    #[serde(default)]
+    pub maxLength: Option<usize>,
+    /// Only capture arguments for these hooks. All wrapped hooks when omitted.
+    #[serde(default)]
+    pub hooks: Option<Vec<String>>,
+    /// Regular expressions whose matches are replaced with `[redacted]`.
+    #[serde(default)]
+    pub redact: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArgumentMode {
+    /// Source text of each argument.
+    #[default]
+    Source,
+    /// Live values for identifiers, member chains and literals, source text otherwise.
+    Value,
+    /// Source text for identifiers and member chains, a kind placeholder otherwise.
+    IdentifiersOnly,
+}
+
+/// Builds the member chain for a `gate` path like `process.env.REACT_JITTER`.
+fn gate_expr(path: &str) -> Expr {
+    let is_ident = |segment: &&str| {
+        segment.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+            && segment.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+    };
+    let segments: Vec<&str> = path.split('.').collect();
+    if !segments.iter().all(is_ident) {
+        panic!("invalid gate {path:?} for react-jitter: expected a dotted path like globalThis.__REACT_JITTER__");
+    }
+
+    segments[1..].iter().fold(
+        Expr::Ident(quote_ident!(segments[0]).into()),
+        |obj, prop| {
+            Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(obj),
+                prop: MemberProp::Ident(quote_ident!(*prop)),
+            })
+        },
+    )
+}
+
+fn default_hash_length() -> usize {
+    8
+}
+
+fn default_runtime_module() -> String {
+    "react-jitter/runtime".into()
+}
+
+fn default_runtime_export() -> String {
+    "useJitterScope".into()
 }
 
 fn default_exclude_patterns() -> Vec<String> {
@@ -131,15 +368,544 @@ impl Visit for ReactFnAnalyzer {
     fn visit_class_expr(&mut self, _: &ClassExpr) {}
 }
 
+/// Whether the arrow or function expression assigned to a binding renders JSX
+/// or calls hooks in its own body.
+fn renders_or_calls_hooks(init: &Expr) -> bool {
+    ReactFnAnalyzer::new().analyze_fn(|analyzer| match init {
+        Expr::Arrow(arrow) => arrow.body.visit_with(analyzer),
+        Expr::Fn(fn_expr) => fn_expr.function.visit_with(analyzer),
+        _ => init.visit_with(analyzer),
+    })
+}
+
+fn warn_nested_component(component: &Ident, outer: &str) {
+    let message = format!(
+        "react-jitter: `{}` is declared inside `{outer}` and remounts on every render of `{outer}`",
+        component.sym
+    );
+    HANDLER.with(|handler| handler.struct_span_warn(component.span, &message).emit());
+}
+
+/// `useSomething`, following the rules of hooks naming.
+fn is_hook_name(name: &str) -> bool {
+    name.starts_with("use") && name.chars().nth(3).is_some_and(|c| c.is_uppercase())
+}
+
+/// Prefix of the module constants holding hoisted metadata.
+const HOISTED_PREFIX: &str = "_jitter";
+
+/// A module without `import`/`export` that uses `require`, `module.exports` or `exports`.
+fn is_commonjs(m: &Module) -> bool {
+    struct Finder {
+        found: bool,
+    }
+
+    impl Visit for Finder {
+        fn visit_call_expr(&mut self, n: &CallExpr) {
+            if matches!(&n.callee, Callee::Expr(callee) if matches!(&**callee, Expr::Ident(id) if id.sym == *"require")) {
+                self.found = true;
+            }
+            n.visit_children_with(self);
+        }
+
+        fn visit_member_expr(&mut self, n: &MemberExpr) {
+            match &*n.obj {
+                Expr::Ident(obj) if obj.sym == *"exports" => self.found = true,
+                Expr::Ident(obj)
+                    if obj.sym == *"module" && matches!(&n.prop, MemberProp::Ident(prop) if prop.sym == *"exports") =>
+                {
+                    self.found = true
+                }
+                _ => {}
+            }
+            n.visit_children_with(self);
+        }
+    }
+
+    if m.body.iter().any(|item| matches!(item, ModuleItem::ModuleDecl(_))) {
+        return false;
+    }
+    let mut finder = Finder { found: false };
+    m.visit_with(&mut finder);
+    finder.found
+}
+
+/// `const __reactJitterInstrumented = true`
+fn marker_declaration() -> ModuleItem {
+    ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        kind: VarDeclKind::Const,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(quote_ident!(MARKER_IDENT).into()),
+            init: Some(Box::new(Expr::Lit(Lit::Bool(true.into())))),
+            definite: false,
+        }],
+        ctxt: SyntaxContext::empty(),
+    }))))
+}
+
+/// Module constant holding the evaluated `gate`.
+const GATE_IDENT: &str = "_jitterEnabled";
+
+/// Module constant declared by every module this plugin instrumented.
+const MARKER_IDENT: &str = "__reactJitterInstrumented";
+
+/// Modules emitted by this plugin declare `__reactJitterInstrumented`.
+fn is_instrumented_module(m: &Module) -> bool {
+    m.body.iter().any(|item| {
+        matches!(item, ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) if var.decls.iter().any(|decl| {
+            matches!(&decl.name, Pat::Ident(binding) if binding.id.sym == *MARKER_IDENT)
+        }))
+    })
+}
+
+/// Where instrumented code imports the runtime from.
+struct Runtime {
+    module: String,
+    /// Export that creates a scope, `useJitterScope` unless configured.
+    export: String,
+}
+
+impl Runtime {
+    /// `const { ... } = require("react-jitter/runtime")`
+    fn require_pattern<'a>(&self, stmt: &'a Stmt) -> Option<&'a ObjectPat> {
+        let Stmt::Decl(Decl::Var(var)) = stmt else {
+            return None;
+        };
+        var.decls.iter().find_map(|decl| match (&decl.name, decl.init.as_deref()) {
+            (
+                Pat::Object(pattern),
+                Some(Expr::Call(CallExpr {
+                    callee: Callee::Expr(callee),
+                    args,
+                    ..
+                })),
+            ) if matches!(&**callee, Expr::Ident(id) if id.sym == *"require")
+                && matches!(args.first(), Some(ExprOrSpread { expr, .. })
+                    if matches!(&**expr, Expr::Lit(Lit::Str(src)) if src.value == *self.module)) =>
+            {
+                Some(pattern)
+            }
+            _ => None,
+        })
+    }
+
+    /// Number of leading directives (`"use client"`), imports and runtime requires,
+    /// after which generated declarations are inserted.
+    fn prologue_len(&self, body: &[ModuleItem]) -> usize {
+        body.iter()
+            .position(|item| match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(..)) => false,
+                ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. })) => !matches!(&**expr, Expr::Lit(Lit::Str(_))),
+                ModuleItem::Stmt(stmt) => self.require_pattern(stmt).is_none(),
+                _ => true,
+            })
+            .unwrap_or(body.len())
+    }
+
+    /// `useJitterScope(...)`, possibly behind a `gate`.
+    fn is_scope_call(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Call(CallExpr {
+                callee: Callee::Expr(callee),
+                ..
+            }) => matches!(&**callee, Expr::Ident(id) if id.sym == *self.export),
+            Expr::Paren(ParenExpr { expr, .. }) | Expr::Cond(CondExpr { cons: expr, .. }) => self.is_scope_call(expr),
+            _ => false,
+        }
+    }
+
+    /// A body that already starts with `const h = useJitterScope(...)`.
+    fn is_instrumented(&self, body: &BlockStmt) -> bool {
+        matches!(body.stmts.first(), Some(Stmt::Decl(Decl::Var(var)))
+            if var.decls.iter().any(|decl| decl.init.as_deref().is_some_and(|init| self.is_scope_call(init))))
+    }
+
+    fn is_function_instrumented(&self, function: &Function) -> bool {
+        function.body.as_ref().is_some_and(|body| self.is_instrumented(body))
+    }
+}
+
+/// `h.<method>(...)`, a call into the scope returned by `useJitterScope`.
+fn is_h_call(expr: &Expr, method: &str) -> bool {
+    let Expr::Call(CallExpr {
+        callee: Callee::Expr(callee),
+        ..
+    }) = expr
+    else {
+        return false;
+    };
+
+    matches!(
+        &**callee,
+        Expr::Member(MemberExpr {
+            obj,
+            prop: MemberProp::Ident(prop),
+            ..
+        }) if prop.sym == *method && matches!(&**obj, Expr::Ident(obj) if obj.sym == *"h")
+    )
+}
+
+fn is_create_context_call(call: &CallExpr) -> bool {
+    if let Callee::Expr(callee_expr) = &call.callee {
+        match &**callee_expr {
+            Expr::Ident(id) => return id.sym == *"createContext",
+            Expr::Member(MemberExpr {
+                obj,
+                prop: MemberProp::Ident(prop_ident),
+                ..
+            }) => {
+                if let Expr::Ident(obj_ident) = &**obj {
+                    return obj_ident.sym == *"React" && prop_ident.sym == *"createContext";
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Identifiers and plain member chains (`Contexts.User`) can be evaluated a second
+/// time without side effects.
+fn is_static_reference(expr: &Expr) -> bool {
+    match expr {
+        Expr::Ident(_) => true,
+        Expr::Member(MemberExpr {
+            obj,
+            prop: MemberProp::Ident(_),
+            ..
+        }) => is_static_reference(obj),
+        _ => false,
+    }
+}
+
+struct ArgumentCapture {
+    mode: ArgumentMode,
+    max_length: Option<usize>,
+    hooks: Option<HashSet<String>>,
+    redact: Vec<Regex>,
+}
+
+impl ArgumentCapture {
+    fn from_config(config: IncludeArguments) -> Option<Self> {
+        let options = match config {
+            IncludeArguments::All(false) => return None,
+            IncludeArguments::All(true) => ArgumentOptions::default(),
+            IncludeArguments::WithOptions(options) => options,
+        };
+
+        let redact = options
+            .redact
+            .iter()
+            .map(|p| {
+                Regex::new(p).unwrap_or_else(|e| panic!("invalid redact pattern {p:?} for react-jitter: {e}"))
+            })
+            .collect();
+
+        Some(Self {
+            mode: options.mode,
+            max_length: options.maxLength,
+            hooks: options.hooks.map(|hooks| hooks.into_iter().collect()),
+            redact,
+        })
+    }
+
+    fn includes_hook(&self, hook: &str) -> bool {
+        self.hooks.as_ref().is_none_or(|hooks| hooks.contains(hook))
+    }
+
+    fn sanitize(&self, text: String) -> String {
+        let mut text = text;
+        for pattern in &self.redact {
+            text = pattern.replace_all(&text, "[redacted]").into_owned();
+        }
+        match self.max_length {
+            Some(max_length) if text.chars().count() > max_length => {
+                let mut truncated: String = text.chars().take(max_length).collect();
+                truncated.push('…');
+                truncated
+            }
+            _ => text,
+        }
+    }
+}
+
+fn expression_kind(expr: &Expr) -> &'static str {
+    match expr {
+        Expr::Lit(Lit::Str(_)) | Expr::Tpl(_) => "<string>",
+        Expr::Lit(Lit::Num(_)) | Expr::Lit(Lit::BigInt(_)) => "<number>",
+        Expr::Lit(Lit::Bool(_)) => "<boolean>",
+        Expr::Lit(Lit::Null(_)) => "<null>",
+        Expr::Object(_) => "<object>",
+        Expr::Array(_) => "<array>",
+        Expr::Arrow(_) | Expr::Fn(_) => "<function>",
+        Expr::Call(_) | Expr::New(_) => "<call>",
+        _ => "<expression>",
+    }
+}
+
+/// Per-component state while its body is being transformed.
+#[derive(Default)]
+struct ComponentScope {
+    name: String,
+    /// A custom hook definition rather than a component.
+    is_hook: bool,
+    /// Name of the enclosing component or hook that defines this one on every render.
+    nested_in: Option<String>,
+    return_count: usize,
+    /// Index into `JitterTransform::scope_records` when coverage is enabled.
+    record_index: Option<usize>,
+    /// Functions nested in the component body that are currently being visited.
+    nested_fn_depth: usize,
+    /// State setters and reducer dispatchers declared in the component body.
+    setters: HashSet<String>,
+    /// `nested_fn_depth` of the body of the dependency-less effect being visited.
+    effect_fn_depth: Option<usize>,
+    /// Conditions and branches enclosing the code being visited.
+    guard_depth: usize,
+    /// The `.map(...)` callback whose body is being visited.
+    map_callback: Option<MapCallback>,
+    /// Scopes with the same name declared before this one, for structural ids.
+    ordinal: usize,
+    /// Calls of each hook seen so far, for structural ids.
+    hook_ordinals: HashMap<String, usize>,
+}
+
+#[derive(Clone)]
+struct MapCallback {
+    /// `nested_fn_depth` of the callback body.
+    fn_depth: usize,
+    /// Name of the index parameter, if the callback declares one.
+    index: Option<String>,
+}
+
+/// The callback of `list.map((item, index) => ...)`.
+fn map_callback(call: &CallExpr) -> Option<Option<String>> {
+    let Callee::Expr(callee) = &call.callee else {
+        return None;
+    };
+    let Expr::Member(MemberExpr {
+        prop: MemberProp::Ident(prop),
+        ..
+    }) = &**callee
+    else {
+        return None;
+    };
+    if prop.sym != *"map" {
+        return None;
+    }
+
+    let index = match &*call.args.first()?.expr {
+        Expr::Arrow(arrow) => arrow.params.get(1),
+        Expr::Fn(fn_expr) => fn_expr.function.params.get(1).map(|param| &param.pat),
+        _ => return None,
+    };
+    Some(match index {
+        Some(Pat::Ident(binding)) => Some(binding.id.sym.to_string()),
+        _ => None,
+    })
+}
+
+/// Whether `expr` reads the binding `name`.
+fn references_ident(expr: &Expr, name: &str) -> bool {
+    struct Finder<'a> {
+        name: &'a str,
+        found: bool,
+    }
+
+    impl Visit for Finder<'_> {
+        fn visit_ident(&mut self, n: &Ident) {
+            self.found |= n.sym == *self.name;
+        }
+
+        // `item.index` does not read a binding called `index`
+        fn visit_member_prop(&mut self, n: &MemberProp) {
+            if let MemberProp::Computed(computed) = n {
+                computed.visit_with(self);
+            }
+        }
+    }
+
+    let mut finder = Finder { name, found: false };
+    expr.visit_with(&mut finder);
+    finder.found
+}
+
+/// What a module instrumented, registered with the runtime for coverage reports.
+struct ScopeRecord {
+    name: String,
+    id: String,
+    line: usize,
+    hooks: Vec<HookRecord>,
+}
+
+struct HookRecord {
+    id: String,
+    hook: String,
+    line: usize,
+}
+
+/// `{ key: value, ... }` with literal values.
+fn literal_object<const N: usize>(props: [(&str, Lit); N]) -> Expr {
+    Expr::Object(ObjectLit {
+        span: DUMMY_SP,
+        props: props
+            .into_iter()
+            .map(|(key, value)| {
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(quote_ident!(key)),
+                    value: Box::new(Expr::Lit(value)),
+                })))
+            })
+            .collect(),
+    })
+}
+
+/// Static metadata moved out of the render path by `metadata: "compact"`, keyed by id.
+#[derive(Default)]
+struct Manifest {
+    scopes: Vec<(String, Vec<PropOrSpread>)>,
+    hooks: Vec<(String, Vec<PropOrSpread>)>,
+}
+
+impl Manifest {
+    fn is_empty(&self) -> bool {
+        self.scopes.is_empty() && self.hooks.is_empty()
+    }
+}
+
+/// Static meta props as a JSON object, for manifests written to disk.
+fn json_object<'a>(props: impl Iterator<Item = &'a PropOrSpread>) -> serde_json::Value {
+    props
+        .filter_map(|prop| match prop {
+            PropOrSpread::Prop(prop) => match &**prop {
+                Prop::KeyValue(KeyValueProp { key, value }) => {
+                    let key = match key {
+                        PropName::Ident(key) => key.sym.to_string(),
+                        PropName::Str(key) => key.value.to_atom_lossy().to_string(),
+                        _ => return None,
+                    };
+                    Some((key, json_value(value)))
+                }
+                _ => None,
+            },
+            PropOrSpread::Spread(_) => None,
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+fn json_value(expr: &Expr) -> serde_json::Value {
+    match expr {
+        Expr::Lit(Lit::Str(value)) => value.value.to_atom_lossy().to_string().into(),
+        // Lines and offsets are whole numbers, which JSON writes without a fraction
+        Expr::Lit(Lit::Num(value)) if value.value.fract() == 0.0 => (value.value as i64).into(),
+        Expr::Lit(Lit::Num(value)) => value.value.into(),
+        Expr::Lit(Lit::Bool(value)) => value.value.into(),
+        Expr::Array(array) => array
+            .elems
+            .iter()
+            .map(|elem| elem.as_ref().map_or(serde_json::Value::Null, |elem| json_value(&elem.expr)))
+            .collect(),
+        Expr::Object(object) => json_object(object.props.iter()),
+        _ => serde_json::Value::Null,
+    }
+}
+
+/// Splits meta object props into those named in `inline` and the rest.
+fn split_props(props: Vec<PropOrSpread>, inline: &[&str]) -> (Vec<PropOrSpread>, Vec<PropOrSpread>) {
+    props
+        .into_iter()
+        .partition(|prop| prop_key(prop).is_some_and(|key| inline.contains(&key)))
+}
+
+/// Hook meta props that have to be evaluated during render.
+fn is_dynamic_meta(prop: &PropOrSpread) -> bool {
+    let PropOrSpread::Prop(prop) = prop else {
+        return true;
+    };
+    match &**prop {
+        Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(key),
+            value,
+        }) => match key.sym.as_ref() {
+            "context" => true,
+            "arguments" => !matches!(&**value, Expr::Array(ArrayLit { elems, .. })
+                if elems.iter().flatten().all(|arg| matches!(&*arg.expr, Expr::Lit(_)))),
+            _ => false,
+        },
+        _ => true,
+    }
+}
+
+fn prop_key(prop: &PropOrSpread) -> Option<&str> {
+    match prop {
+        PropOrSpread::Prop(prop) => match &**prop {
+            Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(key),
+                ..
+            }) => Some(key.sym.as_ref()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// `h.<method>(...args)`, mapped to `span` in the source.
+fn h_method_call(span: Span, method: &str, args: Vec<ExprOrSpread>) -> Expr {
+    Expr::Call(CallExpr {
+        span,
+        callee: MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(Expr::Ident(quote_ident!("h").into())),
+            prop: MemberProp::Ident(quote_ident!(method)),
+        }
+        .as_callee(),
+        args,
+        type_args: None,
+        ctxt: SyntaxContext::empty(),
+    })
+}
 
 struct JitterTransform {
     cm: PluginSourceMapProxy,
-    current_component: Option<Ident>,
+    current_component: Option<ComponentScope>,
+    branch_conditions: HashMap<BytePos, String>,
     file_path: String,
-    ignoreHooks: HashSet<String>,
+    ignore_hooks: HashSet<String>,
     exclude_patterns: Vec<Pattern>,
     instrumented_any_function: bool,
-    include_arguments: bool,
+    tagged_any_context: bool,
+    argument_capture: Option<ArgumentCapture>,
+    timing: Option<TimingOptions>,
+    report_aborted_renders: bool,
+    track_owners: bool,
+    coverage: bool,
+    scope_records: Vec<ScopeRecord>,
+    lint: bool,
+    lint_findings: Vec<LintFinding>,
+    detect_render_loops: bool,
+    track_keys: bool,
+    track_props: bool,
+    count_initializers: bool,
+    compact_metadata: bool,
+    manifest: Manifest,
+    manifest_dir: Option<String>,
+    /// Frozen module-level constants holding static metadata, by name.
+    hoisted: Vec<(String, Expr)>,
+    id_strategy: IdStrategy,
+    /// Scopes of each name seen so far, for structural ids.
+    scope_ordinals: HashMap<String, usize>,
+    /// Contexts of each binding name seen so far, for structural ids.
+    context_ordinals: HashMap<String, usize>,
+    ids: IdRegistry,
+    gate: Option<Expr>,
+    strip: bool,
+    import_style: ImportStyle,
+    is_script: bool,
+    runtime: Runtime,
 }
 
 impl JitterTransform {
@@ -149,15 +915,55 @@ impl JitterTransform {
             .into_iter()
             .filter_map(|p| Pattern::new(&p).ok())
             .collect();
+        if !(4..=64).contains(&options.hashLength) {
+            panic!(
+                "invalid hashLength {} for react-jitter: expected between 4 and 64 hex characters",
+                options.hashLength
+            );
+        }
         
         Self {
             cm,
             current_component: None,
+            branch_conditions: HashMap::new(),
             file_path,
-            ignoreHooks: options.ignoreHooks.into_iter().collect(),
+            // The scope hook is never wrapped, whatever it is called
+            ignore_hooks: options
+                .ignoreHooks
+                .into_iter()
+                .chain([options.runtimeExport.clone()])
+                .collect(),
             exclude_patterns: compiled_patterns,
             instrumented_any_function: false,
-            include_arguments: options.includeArguments,
+            tagged_any_context: false,
+            argument_capture: ArgumentCapture::from_config(options.includeArguments),
+            timing: options.timing.options(),
+            report_aborted_renders: options.reportAbortedRenders,
+            track_owners: options.trackOwners,
+            coverage: options.coverage,
+            scope_records: Vec::new(),
+            lint: options.lint,
+            lint_findings: Vec::new(),
+            detect_render_loops: options.detectRenderLoops,
+            track_keys: options.trackKeys,
+            track_props: options.trackProps,
+            count_initializers: options.countInitializers,
+            compact_metadata: options.metadata == MetadataMode::Compact,
+            manifest: Manifest::default(),
+            manifest_dir: options.manifestDir,
+            hoisted: Vec::new(),
+            id_strategy: options.idStrategy,
+            scope_ordinals: HashMap::new(),
+            context_ordinals: HashMap::new(),
+            ids: IdRegistry::new(options.hashLength, options.namespace),
+            gate: options.gate.as_deref().map(gate_expr),
+            strip: options.mode == Mode::Strip,
+            import_style: options.importStyle,
+            is_script: false,
+            runtime: Runtime {
+                module: options.runtimeModule,
+                export: options.runtimeExport,
+            },
         }
     }
 
@@ -176,13 +982,53 @@ impl JitterTransform {
         false
     }
 
-    fn generate_location_hash(&self, file: &str, line: f64, offset: f64) -> String {
-        use sha2::{Digest, Sha256};
-        let input = format!("{file}:{line}:{offset}");
-        let mut hasher = Sha256::new();
-        hasher.update(input.as_bytes());
-        let result = hasher.finalize();
-        hex::encode(&result[..4]) // Use first 8 characters (4 bytes) of hash
+    /// Id for `input`, warning when different code already has the same id.
+    fn id(&mut self, input: String, span: Span) -> String {
+        let line = self.line_col(span).line;
+        let (id, collision) = self.ids.id(input, line);
+        if let Some(first_line) = collision {
+            let message = format!(
+                "react-jitter: id `{id}` is also used by the code at line {first_line}; increase `hashLength` to tell them apart"
+            );
+            HANDLER.with(|handler| handler.struct_span_warn(span, &message).emit());
+        }
+        id
+    }
+
+    /// Id of the code at `span`, from the file and its line and column.
+    fn location_id(&mut self, span: Span) -> String {
+        let linecol = self.line_col(span);
+        let input = format!("{}:{}:{}", self.file_path, linecol.line, linecol.col_display);
+        self.id(input, span)
+    }
+
+    /// Id of the scope of a component or custom hook called `name`.
+    fn scope_id(&mut self, name: &str, span: Span) -> String {
+        match self.id_strategy {
+            IdStrategy::Location => self.location_id(span),
+            IdStrategy::Structural => {
+                let next = self.scope_ordinals.entry(name.to_string()).or_default();
+                let ordinal = *next;
+                *next += 1;
+                if let Some(component) = self.current_component.as_mut() {
+                    component.ordinal = ordinal;
+                }
+                self.id(format!("{}:{name}:{ordinal}", self.file_path), span)
+            }
+        }
+    }
+
+    /// Id of a call of `hook` in the current component, which is told apart from
+    /// other scopes with the same name by its ordinal.
+    fn hook_id(&mut self, hook: &str, span: Span) -> String {
+        let Some(component) = self.current_component.as_mut().filter(|_| self.id_strategy == IdStrategy::Structural)
+        else {
+            return self.location_id(span);
+        };
+        let ordinal = component.hook_ordinals.entry(hook.to_string()).or_default();
+        let input = format!("{}:{}:{}:{hook}:{ordinal}", self.file_path, component.name, component.ordinal);
+        *ordinal += 1;
+        self.id(input, span)
     }
 
     fn line_col(&self, span: Span) -> Loc {
@@ -191,44 +1037,808 @@ impl JitterTransform {
 
     fn should_wrap_hook(&self, ident: &Ident) -> bool {
         let s = ident.sym.as_ref();
-        if !s.starts_with("use") || self.ignoreHooks.contains(s) {
+        if !s.starts_with("use") || self.ignore_hooks.contains(s) {
             return false;
         }
 
-        s.len() > 3 && s.chars().nth(3).is_some_and(|c| c.is_uppercase())
+        // React 19 `use(Context)` is tracked like `useContext(Context)`
+        s == "use" || (s.len() > 3 && s.chars().nth(3).is_some_and(|c| c.is_uppercase()))
+    }
+
+    /// Starts transforming the body of a component or custom hook and returns
+    /// the enclosing scope, to be restored once the body is done.
+    fn enter_scope(&mut self, ident: &Ident, is_hook: bool) -> Option<ComponentScope> {
+        let nested_in = self
+            .current_component
+            .as_ref()
+            .filter(|_| !is_hook)
+            .map(|outer| outer.name.clone());
+
+        if let Some(outer) = &nested_in {
+            warn_nested_component(ident, outer);
+        }
+
+        self.current_component.replace(ComponentScope {
+            name: ident.sym.to_string(),
+            is_hook,
+            nested_in,
+            ..Default::default()
+        })
+    }
+
+    fn instrument_function_body(&mut self, body: &mut BlockStmt, component_ident: &Ident, span: Span) {
+        self.instrumented_any_function = true;
+        let h_decl = self.scope_declaration(component_ident, span);
+        self.insert_scope_declaration(body, h_decl);
+    }
+
+    /// Instruments the arrow or function expression assigned to `const Component = ...`.
+    fn instrument_component_init(&mut self, comp_ident: &Ident, init_expr: &mut Expr, is_hook: bool) {
+        self.instrumented_any_function = true;
+        let prev_component = self.enter_scope(comp_ident, is_hook);
+
+        match init_expr {
+            Expr::Arrow(arrow) => {
+                let h_decl_stmt = self.scope_declaration(comp_ident, arrow.span);
+
+                match &mut *arrow.body {
+                    BlockStmtOrExpr::BlockStmt(block) => {
+                        block.visit_mut_with(self);
+                        self.insert_scope_declaration(block, h_decl_stmt);
+                    }
+                    BlockStmtOrExpr::Expr(expr) => {
+                        // Moved into a `return`, keeping its own span
+                        expr.visit_mut_with(self);
+                        let span = expr.span();
+                        let arg = if is_hook {
+                            expr.take()
+                        } else {
+                            self.wrap_in_h_re(expr.take(), span)
+                        };
+                        let mut block = BlockStmt {
+                            span,
+                            stmts: vec![Stmt::Return(ReturnStmt {
+                                span,
+                                arg: Some(arg),
+                            })],
+                            ctxt: SyntaxContext::empty(),
+                        };
+                        self.insert_scope_declaration(&mut block, h_decl_stmt);
+                        *arrow.body = BlockStmtOrExpr::BlockStmt(block);
+                    }
+                }
+            }
+            Expr::Fn(fn_expr) => {
+                if let Some(body) = &mut fn_expr.function.body {
+                    self.instrument_function_body(body, comp_ident, fn_expr.function.span);
+                }
+                fn_expr.function.visit_mut_children_with(self);
+            }
+            _ => {
+                init_expr.visit_mut_children_with(self);
+            }
+        }
+        self.current_component = prev_component;
+    }
+
+    /// Puts the scope declaration at the top of `block`. With `reportAbortedRenders`
+    /// the rest of the body is guarded so renders that throw or suspend are reported.
+    fn insert_scope_declaration(&self, block: &mut BlockStmt, h_decl: Stmt) {
+        let is_hook = self.current_component.as_ref().is_some_and(|c| c.is_hook);
+        if !self.report_aborted_renders || is_hook {
+            block.stmts.insert(0, h_decl);
+            return;
+        }
+
+        // The guard maps to the body it replaces
+        let span = block.span;
+        let stmts = std::mem::take(&mut block.stmts);
+        let error_ident: Ident = quote_ident!("error").into();
+        let guard = Stmt::Try(Box::new(TryStmt {
+            span,
+            block: BlockStmt {
+                span,
+                stmts,
+                ctxt: SyntaxContext::empty(),
+            },
+            handler: Some(CatchClause {
+                span,
+                param: Some(Pat::Ident(error_ident.clone().into())),
+                body: BlockStmt {
+                    span,
+                    stmts: vec![
+                        h_method_call(span, "a", vec![error_ident.clone().as_arg()]).into_stmt(),
+                        Stmt::Throw(ThrowStmt {
+                            span,
+                            arg: Box::new(Expr::Ident(error_ident)),
+                        }),
+                    ],
+                    ctxt: SyntaxContext::empty(),
+                },
+            }),
+            finalizer: Some(BlockStmt {
+                span,
+                stmts: vec![h_method_call(span, "f", vec![]).into_stmt()],
+                ctxt: SyntaxContext::empty(),
+            }),
+        }));
+        block.stmts = vec![h_decl, guard];
+    }
+
+    /// `const h = useJitterScope(_jitterScope0)`, with the scope meta hoisted. The
+    /// declaration maps to the component's own `span` so debuggers step onto it.
+    fn scope_declaration(&mut self, component_ident: &Ident, span: Span) -> Stmt {
+        let linecol = self.line_col(span);
+        let hash = self.scope_id(&component_ident.sym, span);
+
+        if self.coverage {
+            if let Some(component) = self.current_component.as_mut() {
+                component.record_index = Some(self.scope_records.len());
+            }
+            self.scope_records.push(ScopeRecord {
+                name: component_ident.sym.to_string(),
+                id: hash.clone(),
+                line: linecol.line,
+                hooks: vec![],
+            });
+        }
+
+        let mut props = vec![
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("name")),
+                value: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: component_ident.sym.clone().into(),
+                    raw: None,
+                }))),
+            }))),
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("id")),
+                value: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: hash.clone().into(),
+                    raw: None,
+                }))),
+            }))),
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("file")),
+                value: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: self.file_path.clone().into(),
+                    raw: None,
+                }))),
+            }))),
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("line")),
+                value: Box::new(Expr::Lit(Lit::Num(Number {
+                    span: DUMMY_SP,
+                    value: linecol.line as f64,
+                    raw: None,
+                }))),
+            }))),
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("offset")),
+                value: Box::new(Expr::Lit(Lit::Num(Number {
+                    span: DUMMY_SP,
+                    value: linecol.col_display as f64,
+                    raw: None,
+                }))),
+            }))),
+        ];
+
+        let is_hook = self.current_component.as_ref().is_some_and(|c| c.is_hook);
+        if is_hook {
+            props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("kind")),
+                value: Box::new(Expr::Lit(Lit::Str("hook".into()))),
+            }))));
+        }
+
+        if let Some(outer) = self.current_component.as_ref().and_then(|c| c.nested_in.as_deref()) {
+            props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("nestedIn")),
+                value: Box::new(Expr::Lit(Lit::Str(outer.into()))),
+            }))));
+        }
+
+        // Location ids are the default and are left implicit
+        if self.id_strategy == IdStrategy::Structural {
+            props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("idStrategy")),
+                value: Box::new(Expr::Lit(Lit::Str("structural".into()))),
+            }))));
+        }
+
+        if let Some(timing) = self.timing.as_ref().filter(|_| !is_hook) {
+            props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("timing")),
+                value: Box::new(Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                        key: PropName::Ident(quote_ident!("marks")),
+                        value: Box::new(Expr::Lit(Lit::Bool(Bool {
+                            span: DUMMY_SP,
+                            value: timing.marks,
+                        }))),
+                    })))],
+                })),
+            }))));
+        }
+
+        let findings: Vec<_> = self
+            .lint_findings
+            .iter()
+            .filter(|finding| span.contains(finding.span))
+            .map(|finding| Some(self.lint_record(finding).as_arg()))
+            .collect();
+        if !findings.is_empty() {
+            props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("lint")),
+                value: Box::new(Expr::Array(ArrayLit {
+                    span: DUMMY_SP,
+                    elems: findings,
+                })),
+            }))));
+        }
+
+        if self.compact_metadata {
+            let (inline, manifest) = split_props(props, &["id", "timing"]);
+            self.manifest.scopes.push((hash, manifest));
+            props = inline;
+        }
+
+        let meta_obj = Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props,
+        });
+
+        let meta = self.hoist("Scope", meta_obj);
+        Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            span,
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls: vec![VarDeclarator {
+                span,
+                name: Pat::Ident(quote_ident!("h").into()),
+                init: Some(Box::new(self.gated(
+                    Expr::Call(CallExpr {
+                        span,
+                        callee: quote_ident!(self.runtime.export.as_str()).as_callee(),
+                        args: vec![meta.as_arg()],
+                        type_args: None,
+                        ctxt: SyntaxContext::empty(),
+                    }),
+                    Expr::Ident(quote_ident!("disabledScope").into()),
+                ))),
+                definite: false,
+            }],
+            ctxt: SyntaxContext::empty(),
+        })))
+    }
+
+    /// `{ rule, name, line }` for a finding of the static lint pass.
+    fn lint_record(&self, finding: &LintFinding) -> Expr {
+        literal_object([
+            ("rule", Lit::Str(finding.rule.into())),
+            ("name", Lit::Str(finding.name.clone().into())),
+            ("line", Lit::Num((self.line_col(finding.span).line as f64).into())),
+        ])
+    }
+
+    fn tag_context(&mut self, call: Box<Expr>, binding: &Ident) -> Box<Expr> {
+        self.tagged_any_context = true;
+        let linecol = self.line_col(call.span());
+        let hash = match self.id_strategy {
+            IdStrategy::Location => self.location_id(call.span()),
+            // Contexts created in functions can share a binding name
+            IdStrategy::Structural => {
+                let next = self.context_ordinals.entry(binding.sym.to_string()).or_default();
+                let ordinal = *next;
+                *next += 1;
+                self.id(format!("{}:context:{}:{ordinal}", self.file_path, binding.sym), call.span())
+            }
+        };
+
+        let props = vec![
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("id")),
+                value: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: hash.into(),
+                    raw: None,
+                }))),
+            }))),
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("displayName")),
+                value: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: binding.sym.clone().into(),
+                    raw: None,
+                }))),
+            }))),
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("file")),
+                value: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: self.file_path.clone().into(),
+                    raw: None,
+                }))),
+            }))),
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("line")),
+                value: Box::new(Expr::Lit(Lit::Num(Number {
+                    span: DUMMY_SP,
+                    value: linecol.line as f64,
+                    raw: None,
+                }))),
+            }))),
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("offset")),
+                value: Box::new(Expr::Lit(Lit::Num(Number {
+                    span: DUMMY_SP,
+                    value: linecol.col_display as f64,
+                    raw: None,
+                }))),
+            }))),
+        ];
+
+        Box::new(Expr::Call(CallExpr {
+            span: call.span(),
+            callee: quote_ident!("tagContext").as_callee(),
+            args: vec![
+                call.as_arg(),
+                Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props,
+                })
+                .as_arg(),
+            ],
+            type_args: None,
+            ctxt: SyntaxContext::empty(),
+        }))
+    }
+
+    fn capture_argument(&self, capture: &ArgumentCapture, arg: &ExprOrSpread) -> Expr {
+        if arg.spread.is_none() && capture.mode == ArgumentMode::Value {
+            match &*arg.expr {
+                // String literals are known at compile time, so they are redacted and truncated
+                Expr::Lit(Lit::Str(value)) => {
+                    return Expr::Lit(Lit::Str(Str {
+                        span: DUMMY_SP,
+                        value: capture.sanitize(value.value.to_atom_lossy().to_string()).into(),
+                        raw: None,
+                    }));
+                }
+                Expr::Lit(Lit::Num(_) | Lit::Bool(_) | Lit::Null(_)) => return *arg.expr.clone(),
+                expr if is_static_reference(expr) => return *arg.expr.clone(),
+                _ => {}
+            }
+        }
+
+        let text = if capture.mode == ArgumentMode::IdentifiersOnly && !is_static_reference(&arg.expr) {
+            expression_kind(&arg.expr).to_string()
+        } else {
+            self.cm
+                .span_to_snippet(arg.expr.span())
+                .unwrap_or_else(|_| "<unsupported>".to_string())
+        };
+        let text = if arg.spread.is_some() {
+            format!("...{text}")
+        } else {
+            text
+        };
+
+        Expr::Lit(Lit::Str(Str {
+            span: DUMMY_SP,
+            value: capture.sanitize(text).into(),
+            raw: None,
+        }))
+    }
+
+    fn runtime_import_names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        if self.instrumented_any_function {
+            names.push(self.runtime.export.as_str());
+            if self.gate.is_some() {
+                names.push("disabledScope");
+            }
+        }
+        if self.tagged_any_context {
+            names.push("tagContext");
+        }
+        if !self.scope_records.is_empty() {
+            names.push("registerScopes");
+        }
+        if !self.manifest.is_empty() {
+            names.push("registerManifest");
+        }
+        names
+    }
+
+    /// `_jitterEnabled ? enabled : disabled` when a `gate` is configured. The gate is
+    /// read once per module, so hooks are still called in the same order on every render.
+    fn gated(&self, enabled: Expr, disabled: Expr) -> Expr {
+        if self.gate.is_none() {
+            return enabled;
+        }
+
+        Expr::Paren(ParenExpr {
+            span: enabled.span(),
+            expr: Box::new(Expr::Cond(CondExpr {
+                span: enabled.span(),
+                test: Box::new(Expr::Ident(quote_ident!(GATE_IDENT).into())),
+                cons: Box::new(enabled),
+                alt: Box::new(disabled),
+            })),
+        })
+    }
+
+    /// `const _jitterEnabled = !!(gate)`
+    fn gate_declaration(&self, gate: Expr) -> ModuleItem {
+        let not = |arg: Expr| {
+            Expr::Unary(UnaryExpr {
+                span: DUMMY_SP,
+                op: UnaryOp::Bang,
+                arg: Box::new(arg),
+            })
+        };
+        ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            span: DUMMY_SP,
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls: vec![VarDeclarator {
+                span: DUMMY_SP,
+                name: Pat::Ident(quote_ident!(GATE_IDENT).into()),
+                init: Some(Box::new(not(not(gate)))),
+                definite: false,
+            }],
+            ctxt: SyntaxContext::empty(),
+        }))))
+    }
+
+    /// `import { ... } from "react-jitter/runtime"`, or `const { ... } = require(...)`
+    /// for CommonJS, with the runtime exports the module does not import yet.
+    fn runtime_import(&self, body: &[ModuleItem], commonjs: bool) -> Option<ModuleItem> {
+        let mut missing = self.runtime_import_names();
+        for item in body {
+            let imported: Vec<&Ident> = match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl { src, specifiers, .. }))
+                    if src.value == *self.runtime.module =>
+                {
+                    specifiers
+                        .iter()
+                        .filter_map(|s| match s {
+                            ImportSpecifier::Named(n) => Some(&n.local),
+                            _ => None,
+                        })
+                        .collect()
+                }
+                ModuleItem::Stmt(stmt) => self
+                    .runtime
+                    .require_pattern(stmt)
+                    .map(|pat| {
+                        pat.props
+                            .iter()
+                            .filter_map(|prop| match prop {
+                                ObjectPatProp::Assign(assign) => Some(&assign.key.id),
+                                ObjectPatProp::KeyValue(KeyValuePatProp { value, .. }) => match &**value {
+                                    Pat::Ident(binding) => Some(&binding.id),
+                                    _ => None,
+                                },
+                                ObjectPatProp::Rest(_) => None,
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                _ => vec![],
+            };
+            missing.retain(|name| !imported.iter().any(|local| local.sym == **name));
+        }
+        if missing.is_empty() {
+            return None;
+        }
+
+        let src = Str {
+            span: DUMMY_SP,
+            value: self.runtime.module.as_str().into(),
+            raw: None,
+        };
+        if commonjs {
+            let pattern = ObjectPat {
+                span: DUMMY_SP,
+                props: missing
+                    .into_iter()
+                    .map(|name| {
+                        ObjectPatProp::Assign(AssignPatProp {
+                            span: DUMMY_SP,
+                            key: quote_ident!(name).into(),
+                            value: None,
+                        })
+                    })
+                    .collect(),
+                optional: false,
+                type_ann: None,
+            };
+            let require = Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: quote_ident!("require").as_callee(),
+                args: vec![Expr::Lit(Lit::Str(src)).as_arg()],
+                type_args: None,
+                ctxt: SyntaxContext::empty(),
+            });
+            return Some(ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                span: DUMMY_SP,
+                kind: VarDeclKind::Const,
+                declare: false,
+                decls: vec![VarDeclarator {
+                    span: DUMMY_SP,
+                    name: Pat::Object(pattern),
+                    init: Some(Box::new(require)),
+                    definite: false,
+                }],
+                ctxt: SyntaxContext::empty(),
+            })))));
+        }
+
+        Some(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+            span: DUMMY_SP,
+            specifiers: missing
+                .into_iter()
+                .map(|name| {
+                    ImportSpecifier::Named(ImportNamedSpecifier {
+                        span: DUMMY_SP,
+                        local: quote_ident!(name).into(),
+                        imported: None,
+                        is_type_only: false,
+                    })
+                })
+                .collect(),
+            src: Box::new(src),
+            type_only: false,
+            with: None,
+            phase: Default::default(),
+        })))
+    }
+
+    /// Moves static metadata to a frozen module-level constant so it is allocated
+    /// once instead of on every render, and returns a reference to it.
+    fn hoist(&mut self, kind: &str, value: Expr) -> Expr {
+        let name = format!("{HOISTED_PREFIX}{kind}{}", self.hoisted.len());
+        self.hoisted.push((name.clone(), value));
+        Expr::Ident(quote_ident!(name.as_str()).into())
+    }
+
+    /// `const _jitterHook0 = Object.freeze({ ... })` for every hoisted constant.
+    fn hoisted_declarations(&mut self) -> Vec<ModuleItem> {
+        self.hoisted
+            .drain(..)
+            .map(|(name, value)| {
+                let frozen = Expr::Call(CallExpr {
+                    span: DUMMY_SP,
+                    callee: MemberExpr {
+                        span: DUMMY_SP,
+                        obj: Box::new(Expr::Ident(quote_ident!("Object").into())),
+                        prop: MemberProp::Ident(quote_ident!("freeze")),
+                    }
+                    .as_callee(),
+                    args: vec![value.as_arg()],
+                    type_args: None,
+                    ctxt: SyntaxContext::empty(),
+                });
+                ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                    span: DUMMY_SP,
+                    kind: VarDeclKind::Const,
+                    declare: false,
+                    decls: vec![VarDeclarator {
+                        span: DUMMY_SP,
+                        name: Pat::Ident(quote_ident!(name.as_str()).into()),
+                        init: Some(Box::new(frozen)),
+                        definite: false,
+                    }],
+                    ctxt: SyntaxContext::empty(),
+                }))))
+            })
+            .collect()
+    }
+
+    /// `registerManifest({ file, scopes: { [id]: meta }, hooks: { [id]: meta } })`, with
+    /// the file every entry shares registered once instead of repeated in each.
+    fn manifest_registration(&self) -> ModuleItem {
+        let table = |entries: &[(String, Vec<PropOrSpread>)]| {
+            Box::new(Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: entries
+                    .iter()
+                    .map(|(id, props)| {
+                        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                            key: PropName::Str(id.as_str().into()),
+                            value: Box::new(Expr::Object(ObjectLit {
+                                span: DUMMY_SP,
+                                props: props.iter().filter(|prop| prop_key(prop) != Some("file")).cloned().collect(),
+                            })),
+                        })))
+                    })
+                    .collect(),
+            }))
+        };
+
+        let manifest = Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: [
+                ("file", Box::new(Expr::Lit(Lit::Str(self.file_path.as_str().into())))),
+                ("scopes", table(&self.manifest.scopes)),
+                ("hooks", table(&self.manifest.hooks)),
+            ]
+            .into_iter()
+            .map(|(key, value)| {
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(quote_ident!(key)),
+                    value,
+                })))
+            })
+            .collect(),
+        });
+
+        ModuleItem::Stmt(
+            Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: quote_ident!("registerManifest").as_callee(),
+                args: vec![manifest.as_arg()],
+                type_args: None,
+                ctxt: SyntaxContext::empty(),
+            })
+            .into_stmt(),
+        )
+    }
+
+    /// The manifest as `registerManifest` takes it, for `manifestDir`.
+    fn manifest_json(&self) -> serde_json::Value {
+        let table = |entries: &[(String, Vec<PropOrSpread>)]| {
+            entries
+                .iter()
+                .map(|(id, props)| {
+                    let props = props.iter().filter(|prop| prop_key(prop) != Some("file"));
+                    (id.clone(), json_object(props))
+                })
+                .collect::<serde_json::Map<_, _>>()
+        };
+        serde_json::json!({
+            "file": self.file_path,
+            "scopes": table(&self.manifest.scopes),
+            "hooks": table(&self.manifest.hooks),
+        })
+    }
+
+    /// Writes the manifest to `<dir>/<file>.json`. Inside the wasm sandbox swc maps
+    /// the working directory to `/cwd`.
+    fn write_manifest(&self, dir: &str) -> std::io::Result<()> {
+        let root = if cfg!(target_arch = "wasm32") { "/cwd" } else { "." };
+        let file: PathBuf = Path::new(&self.file_path)
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect();
+        let mut path = Path::new(root).join(dir).join(file).into_os_string();
+        path.push(".json");
+        let path = PathBuf::from(path);
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&self.manifest_json())?;
+        std::fs::write(path, json + "\n")
+    }
+
+    /// `registerScopes(file, [{ name, id, line, hooks: [{ id, hook, line }] }])`
+    fn coverage_registration(&self) -> ModuleItem {
+        let str_lit = |value: &str| {
+            Box::new(Expr::Lit(Lit::Str(Str {
+                span: DUMMY_SP,
+                value: value.into(),
+                raw: None,
+            })))
+        };
+        let num_lit = |value: usize| {
+            Box::new(Expr::Lit(Lit::Num(Number {
+                span: DUMMY_SP,
+                value: value as f64,
+                raw: None,
+            })))
+        };
+        let object = |props: Vec<(&str, Box<Expr>)>| {
+            Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: props
+                    .into_iter()
+                    .map(|(key, value)| {
+                        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                            key: PropName::Ident(quote_ident!(key)),
+                            value,
+                        })))
+                    })
+                    .collect(),
+            })
+        };
+
+        let scopes = self
+            .scope_records
+            .iter()
+            .map(|scope| {
+                let hooks = scope
+                    .hooks
+                    .iter()
+                    .map(|hook| {
+                        Some(
+                            object(vec![
+                                ("id", str_lit(&hook.id)),
+                                ("hook", str_lit(&hook.hook)),
+                                ("line", num_lit(hook.line)),
+                            ])
+                            .as_arg(),
+                        )
+                    })
+                    .collect();
+                Some(
+                    object(vec![
+                        ("name", str_lit(&scope.name)),
+                        ("id", str_lit(&scope.id)),
+                        ("line", num_lit(scope.line)),
+                        (
+                            "hooks",
+                            Box::new(Expr::Array(ArrayLit {
+                                span: DUMMY_SP,
+                                elems: hooks,
+                            })),
+                        ),
+                    ])
+                    .as_arg(),
+                )
+            })
+            .collect();
+
+        ModuleItem::Stmt(
+            Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: quote_ident!("registerScopes").as_callee(),
+                args: vec![
+                    str_lit(&self.file_path).as_arg(),
+                    Expr::Array(ArrayLit {
+                        span: DUMMY_SP,
+                        elems: scopes,
+                    })
+                    .as_arg(),
+                ],
+                type_args: None,
+                ctxt: SyntaxContext::empty(),
+            })
+            .into_stmt(),
+        )
+    }
+
+    /// Source text of `span` on a single line, cut to `max_length` characters.
+    fn short_snippet(&self, span: Span, max_length: usize) -> Option<String> {
+        let snippet = self.cm.span_to_snippet(span).ok()?;
+        let snippet = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+        if snippet.chars().count() > max_length {
+            let mut truncated: String = snippet.chars().take(max_length).collect();
+            truncated.push('…');
+            return Some(truncated);
+        }
+        Some(snippet)
     }
 
-    fn instrument_function_body(&mut self, body: &mut BlockStmt, component_ident: &Ident, span: Span) {
-        self.instrumented_any_function = true;
-        let linecol = self.line_col(span);
-        let hash = self.generate_location_hash(
-            &self.file_path,
-            linecol.line as f64,
-            linecol.col_display as f64,
-        );
+    fn return_branch(&mut self, return_span: Span) -> Expr {
+        let index = self.current_component.as_mut().map_or(0, |component| {
+            component.return_count += 1;
+            component.return_count - 1
+        });
+        let linecol = self.line_col(return_span);
 
-        let props = vec![
-            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                key: PropName::Ident(quote_ident!("name")),
-                value: Box::new(Expr::Lit(Lit::Str(Str {
-                    span: DUMMY_SP,
-                    value: component_ident.sym.clone().into(),
-                    raw: None,
-                }))),
-            }))),
-            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                key: PropName::Ident(quote_ident!("id")),
-                value: Box::new(Expr::Lit(Lit::Str(Str {
-                    span: DUMMY_SP,
-                    value: hash.into(),
-                    raw: None,
-                }))),
-            }))),
+        let mut props = vec![
             PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                key: PropName::Ident(quote_ident!("file")),
-                value: Box::new(Expr::Lit(Lit::Str(Str {
+                key: PropName::Ident(quote_ident!("index")),
+                value: Box::new(Expr::Lit(Lit::Num(Number {
                     span: DUMMY_SP,
-                    value: self.file_path.clone().into(),
+                    value: index as f64,
                     raw: None,
                 }))),
             }))),
@@ -240,43 +1850,215 @@ impl JitterTransform {
                     raw: None,
                 }))),
             }))),
-            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                key: PropName::Ident(quote_ident!("offset")),
-                value: Box::new(Expr::Lit(Lit::Num(Number {
+        ];
+        if let Some(condition) = self.branch_conditions.remove(&return_span.lo()) {
+            props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("condition")),
+                value: Box::new(Expr::Lit(Lit::Str(Str {
                     span: DUMMY_SP,
-                    value: linecol.col_display as f64,
+                    value: condition.into(),
                     raw: None,
                 }))),
-            }))),
-        ];
-
-        let meta_obj = Expr::Object(ObjectLit {
-            span: DUMMY_SP,
-            props,
-        });
+            }))));
+        }
 
-        let h_decl = Stmt::Decl(Decl::Var(Box::new(VarDecl {
-            span: DUMMY_SP,
-            kind: VarDeclKind::Const,
-            declare: false,
-            decls: vec![VarDeclarator {
+        self.hoist(
+            "Branch",
+            Expr::Object(ObjectLit {
                 span: DUMMY_SP,
-                name: Pat::Ident(quote_ident!("h").into()),
-                init: Some(Box::new(Expr::Call(CallExpr {
-                    span: DUMMY_SP,
-                    callee: quote_ident!("useJitterScope").as_callee(),
-                    args: vec![meta_obj.as_arg()],
-                    type_args: None,
-                    ctxt: SyntaxContext::empty(),
-                }))),
-                definite: false,
-            }],
-            ctxt: SyntaxContext::empty(),
-        })));
-        body.stmts.insert(0, h_decl);
+                props,
+            }),
+        )
+    }
+
+    /// Warns about `useState(compute())` and `useReducer(reducer, compute())`, whose
+    /// initializer runs on every render although only the first result is used.
+    /// With `countInitializers` the initializer is wrapped in
+    /// `h.i(value, { id, hook, source, line })` so the runtime counts the discarded runs.
+    fn check_state_initializer(&mut self, call: &mut CallExpr) {
+        let Callee::Expr(callee) = &call.callee else {
+            return;
+        };
+        let hook = match &**callee {
+            Expr::Ident(id) => id.sym.clone(),
+            Expr::Member(MemberExpr {
+                obj,
+                prop: MemberProp::Ident(prop),
+                ..
+            }) if matches!(&**obj, Expr::Ident(obj) if obj.sym == *"React") => prop.sym.clone(),
+            _ => return,
+        };
+        let initializer_index = match hook.as_ref() {
+            "useState" => 0,
+            "useReducer" => 1,
+            _ => return,
+        };
+
+        let Some(ExprOrSpread { spread: None, expr: initializer }) = call.args.get_mut(initializer_index) else {
+            return;
+        };
+        if !matches!(&**initializer, Expr::Call(_) | Expr::New(_)) {
+            return;
+        }
+
+        let source = self.short_snippet(initializer.span(), 40).unwrap_or_default();
+        let message = if hook == *"useState" {
+            format!("react-jitter: `{source}` runs on every render but only its first result is used; pass `() => {source}` to `useState` instead")
+        } else {
+            format!("react-jitter: `{source}` runs on every render but only its first result is used; pass an init function as the third argument of `useReducer` instead")
+        };
+        HANDLER.with(|handler| handler.struct_span_warn(initializer.span(), &message).emit());
+
+        if !self.count_initializers {
+            return;
+        }
+
+        let linecol = self.line_col(initializer.span());
+        let id = self.location_id(initializer.span());
+        let site = literal_object([
+            ("id", Lit::Str(id.into())),
+            ("hook", Lit::Str(hook.as_ref().into())),
+            ("source", Lit::Str(source.into())),
+            ("line", Lit::Num((linecol.line as f64).into())),
+        ]);
+        let site = self.hoist("Site", site);
+        let initializer_span = initializer.span();
+        *initializer = Box::new(h_method_call(
+            initializer_span,
+            "i",
+            vec![initializer.take().as_arg(), site.as_arg()],
+        ));
+    }
+
+    /// `(h.l({ id, setter, line, offset, kind }), setter(...))` for a state setter
+    /// called during render or in an effect without dependencies.
+    fn tag_setter_call(&mut self, expr: &Expr) -> Option<Expr> {
+        let component = self.current_component.as_ref()?;
+        let Expr::Call(call) = expr else {
+            return None;
+        };
+        let Callee::Expr(callee) = &call.callee else {
+            return None;
+        };
+        let Expr::Ident(setter) = &**callee else {
+            return None;
+        };
+        if !component.setters.contains(setter.sym.as_ref()) {
+            return None;
+        }
+
+        let kind = if component.nested_fn_depth == 0 {
+            "render"
+        } else if component.effect_fn_depth == Some(component.nested_fn_depth) {
+            "effect"
+        } else {
+            return None;
+        };
+
+        if component.guard_depth == 0 {
+            let message = if kind == "render" {
+                format!("react-jitter: `{}` is called unconditionally during render and can cause a render loop", setter.sym)
+            } else {
+                format!("react-jitter: `{}` is called in an effect without a dependency array and can cause a render loop", setter.sym)
+            };
+            HANDLER.with(|handler| handler.struct_span_warn(call.span, &message).emit());
+        }
+
+        let linecol = self.line_col(call.span);
+        let id = self.location_id(call.span);
+        let site = literal_object([
+            ("id", Lit::Str(id.into())),
+            ("setter", Lit::Str(setter.sym.as_ref().into())),
+            ("line", Lit::Num((linecol.line as f64).into())),
+            ("offset", Lit::Num((linecol.col_display as f64).into())),
+            ("kind", Lit::Str(kind.into())),
+        ]);
+        let site = self.hoist("Site", site);
+
+        Some(Expr::Seq(SeqExpr {
+            span: call.span,
+            exprs: vec![Box::new(h_method_call(call.span, "l", vec![site.as_arg()])), Box::new(expr.clone())],
+        }))
+    }
+
+    /// Wraps the non-literal props of a child component element in
+    /// `h.p(value, { id, element, prop, line })`.
+    fn track_element_props(&mut self, n: &mut JSXOpeningElement) {
+        let element = match &n.name {
+            JSXElementName::Ident(ident) if ident.sym.chars().next().is_some_and(|c| c.is_uppercase()) => {
+                ident.sym.to_string()
+            }
+            JSXElementName::JSXMemberExpr(_) => match self.short_snippet(n.name.span(), 40) {
+                Some(name) => name,
+                None => return,
+            },
+            _ => return,
+        };
+
+        let linecol = self.line_col(n.span);
+        let id = self.location_id(n.span);
+
+        for attr in n.attrs.iter_mut() {
+            let JSXAttrOrSpread::JSXAttr(JSXAttr {
+                name: JSXAttrName::Ident(prop),
+                value: Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
+                    expr: JSXExpr::Expr(value),
+                    ..
+                })),
+                ..
+            }) = attr
+            else {
+                continue;
+            };
+
+            if prop.sym == *"key" || prop.sym == *"ref" || matches!(&**value, Expr::Lit(_)) {
+                continue;
+            }
+
+            let site = literal_object([
+                ("id", Lit::Str(id.as_str().into())),
+                ("element", Lit::Str(element.as_str().into())),
+                ("prop", Lit::Str(prop.sym.as_ref().into())),
+                ("line", Lit::Num((linecol.line as f64).into())),
+            ]);
+            let site = self.hoist("Site", site);
+            *value = Box::new(h_method_call(value.span(), "p", vec![value.take().as_arg(), site.as_arg()]));
+        }
     }
 
-    fn wrap_in_h_re(&self, expr: Box<Expr>) -> Box<Expr> {
+    /// `h.k(key, { id, source, line })` for the `key` of an element rendered by a `.map` callback.
+    fn track_key(&mut self, key: Box<Expr>, map_callback: &MapCallback) -> Box<Expr> {
+        let linecol = self.line_col(key.span());
+        let id = self.location_id(key.span());
+        let source = self.short_snippet(key.span(), 40).unwrap_or_default();
+
+        if let Some(index) = &map_callback.index {
+            if references_ident(&key, index) {
+                let message = format!(
+                    "react-jitter: `key={{{source}}}` uses the list index; items remount when the list is reordered"
+                );
+                HANDLER.with(|handler| handler.struct_span_warn(key.span(), &message).emit());
+            }
+        }
+
+        let site = literal_object([
+            ("id", Lit::Str(id.into())),
+            ("source", Lit::Str(source.into())),
+            ("line", Lit::Num((linecol.line as f64).into())),
+        ]);
+        let site = self.hoist("Site", site);
+
+        Box::new(h_method_call(key.span(), "k", vec![key.as_arg(), site.as_arg()]))
+    }
+
+    /// `h.re(expr, branch)` for a return of the component's own body.
+    fn wrap_in_h_re(&mut self, expr: Box<Expr>, return_span: Span) -> Box<Expr> {
+        let branch = self.return_branch(return_span);
+        let expr = if self.track_owners {
+            Box::new(h_method_call(expr.span(), "o", vec![expr.as_arg()]))
+        } else {
+            expr
+        };
         Box::new(Expr::Call(CallExpr {
             span: expr.span(),
             callee: MemberExpr {
@@ -285,7 +2067,7 @@ impl JitterTransform {
                 prop: MemberProp::Ident(quote_ident!("re")),
             }
             .as_callee(),
-            args: vec![expr.as_arg()],
+            args: vec![expr.as_arg(), branch.as_arg()],
             type_args: None,
             ctxt: SyntaxContext::empty(),
         }))
@@ -298,62 +2080,99 @@ impl VisitMut for JitterTransform {
             return;
         }
 
+        if self.strip {
+            strip::strip_module(m, &self.runtime);
+            return;
+        }
+
+        // Running the plugin twice over the same code must not instrument it twice
+        if is_instrumented_module(m) {
+            return;
+        }
+
+        if self.lint {
+            self.lint_findings = lint::lint_module(m);
+            HANDLER.with(|handler| {
+                for finding in &self.lint_findings {
+                    handler.struct_span_warn(finding.span, &finding.message()).emit();
+                }
+            });
+        }
+
         m.visit_mut_children_with(self);
 
-        if self.instrumented_any_function {
-            let mut has_import = false;
-            for item in m.body.iter() {
-                if let ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
-                    src, specifiers, ..
-                })) = item
-                {
-                    if src.value == *"react-jitter/runtime"
-                        && specifiers.iter().any(|s| match s {
-                            ImportSpecifier::Named(n) => n.local.sym == *"useJitterScope",
-                            _ => false,
-                        }) {
-                            has_import = true;
-                        }
+        if !self.scope_records.is_empty() {
+            m.body.push(self.coverage_registration());
+        }
+
+        // Written out, the manifest leaves nothing but ids in the module
+        if let Some(dir) = self.manifest_dir.clone().filter(|_| !self.manifest.is_empty()) {
+            match self.write_manifest(&dir) {
+                Ok(()) => self.manifest = Manifest::default(),
+                Err(error) => {
+                    let message = format!(
+                        "react-jitter: could not write the manifest of {} to {dir}, so it is registered in the module: {error}",
+                        self.file_path
+                    );
+                    HANDLER.with(|handler| handler.struct_warn(&message).emit());
                 }
             }
-            if !has_import {
-                let import = ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
-                    span: DUMMY_SP,
-                    specifiers: vec![ImportSpecifier::Named(ImportNamedSpecifier {
-                        span: DUMMY_SP,
-                        local: quote_ident!("useJitterScope").into(),
-                        imported: None,
-                        is_type_only: false,
-                    })],
-                    src: Box::new(Str {
-                        span: DUMMY_SP,
-                        value: "react-jitter/runtime".into(),
-                        raw: None,
-                    }),
-                    type_only: false,
-                    with: None,
-                    phase: Default::default(),
-                }));
-                let idx = m
-                    .body
-                    .iter()
-                    .position(|item| !matches!(item, ModuleItem::ModuleDecl(ModuleDecl::Import(..))))
-                    .unwrap_or(m.body.len());
-                m.body.insert(idx, import);
-            }
         }
+
+        // Registered before anything in the module can render
+        let idx = self.runtime.prologue_len(&m.body);
+        if !self.manifest.is_empty() {
+            let registration = self.manifest_registration();
+            m.body.insert(idx, registration);
+        }
+        if let Some(gate) = self.gate.clone().filter(|_| self.instrumented_any_function) {
+            m.body.insert(idx, self.gate_declaration(gate));
+        }
+        let hoisted = self.hoisted_declarations();
+        m.body.splice(idx..idx, hoisted);
+        if !self.runtime_import_names().is_empty() {
+            m.body.insert(idx, marker_declaration());
+        }
+
+        // Scripts cannot contain an `import`, whatever `importStyle` asks for
+        let commonjs = self.is_script
+            || match self.import_style {
+                ImportStyle::Esm => false,
+                ImportStyle::Cjs => true,
+                ImportStyle::Auto => is_commonjs(m),
+            };
+        if let Some(import) = self.runtime_import(&m.body, commonjs) {
+            let idx = self.runtime.prologue_len(&m.body);
+            m.body.insert(idx, import);
+        }
+    }
+
+    /// Scripts have no imports, so they are instrumented like a module and get a
+    /// `require` for the runtime.
+    fn visit_mut_script(&mut self, s: &mut Script) {
+        let mut module = Module {
+            span: s.span,
+            body: s.body.take().into_iter().map(ModuleItem::Stmt).collect(),
+            shebang: s.shebang.take(),
+        };
+        self.is_script = true;
+        self.visit_mut_module(&mut module);
+
+        s.shebang = module.shebang;
+        s.body = module.body.into_iter().filter_map(ModuleItem::stmt).collect();
     }
 
     fn visit_mut_fn_decl(&mut self, n: &mut FnDecl) {
         let ident_name = n.ident.sym.as_ref();
         let is_component = ident_name.chars().next().is_some_and(|c| c.is_uppercase());
+        let is_hook = is_hook_name(ident_name);
 
-        if is_component
+        if (is_component || is_hook)
+            && !self.runtime.is_function_instrumented(&n.function)
             && ReactFnAnalyzer::new().analyze_fn(|analyzer| {
                 n.function.visit_with(analyzer);
             }) {
-                let prev_component = self.current_component.clone();
-                self.current_component = Some(n.ident.clone());
+                let prev_component = self.enter_scope(&n.ident, is_hook);
 
                 if let Some(body) = &mut n.function.body {
                     self.instrument_function_body(body, &n.ident, n.function.span);
@@ -373,20 +2192,21 @@ impl VisitMut for JitterTransform {
                 Some(id) => id.sym.chars().next().is_some_and(|c| c.is_uppercase()),
                 None => true, // Anonymous functions are often components
             };
+            let is_hook = fn_expr.ident.as_ref().is_some_and(|id| is_hook_name(id.sym.as_ref()));
 
-            if is_component
+            if (is_component || is_hook)
+                && !self.runtime.is_function_instrumented(&fn_expr.function)
                 && ReactFnAnalyzer::new().analyze_fn(|analyzer| {
                     fn_expr.function.visit_with(analyzer);
                 }) {
                     let ident = fn_expr.ident.clone().unwrap_or_else(|| quote_ident!("(anonymous)").into());
-                    let prev_component = self.current_component.clone();
-                    self.current_component = Some(ident.clone());
+                    let prev_component = self.enter_scope(&ident, is_hook);
                     
                     if let Some(body) = &mut fn_expr.function.body {
                         self.instrument_function_body(body, &ident, fn_expr.function.span);
                     }
                     
-                    fn_expr.visit_mut_children_with(self);
+                    fn_expr.function.visit_mut_children_with(self);
                     self.current_component = prev_component;
                     return;
                 }
@@ -400,13 +2220,14 @@ impl VisitMut for JitterTransform {
             Decl::Fn(fn_decl) => {
                 let ident_name = fn_decl.ident.sym.as_ref();
                 let is_component = ident_name.chars().next().is_some_and(|c| c.is_uppercase());
+                let is_hook = is_hook_name(ident_name);
 
-                if is_component
+                if (is_component || is_hook)
+                    && !self.runtime.is_function_instrumented(&fn_decl.function)
                     && ReactFnAnalyzer::new().analyze_fn(|analyzer| {
                         fn_decl.function.visit_with(analyzer);
                     }) {
-                        let prev_component = self.current_component.clone();
-                        self.current_component = Some(fn_decl.ident.clone());
+                        let prev_component = self.enter_scope(&fn_decl.ident, is_hook);
             
                         if let Some(body) = &mut fn_decl.function.body {
                             self.instrument_function_body(body, &fn_decl.ident, fn_decl.function.span);
@@ -425,108 +2246,20 @@ impl VisitMut for JitterTransform {
                         let comp_ident = binding_ident.id.clone();
                         let ident_name = comp_ident.sym.as_ref();
                         let is_component = ident_name.chars().next().is_some_and(|c| c.is_uppercase());
+                        let is_hook = is_hook_name(ident_name);
 
-                        if is_component {
+                        if is_component || is_hook {
                             if let Some(init_expr) = &mut var.init {
-                                if ReactFnAnalyzer::new().analyze_fn(|analyzer| {
-                                    init_expr.visit_with(analyzer);
-                                }) {
-                                    self.instrumented_any_function = true;
-                                    let prev_component = self.current_component.clone();
-                                    self.current_component = Some(comp_ident.clone());
-                                   
-                                    match &mut **init_expr {
-                                        Expr::Arrow(arrow) => {
-                                             let h_decl_stmt = {
-                                                let linecol = self.line_col(arrow.span);
-                                                let hash = self.generate_location_hash(
-                                                    &self.file_path,
-                                                    linecol.line as f64,
-                                                    linecol.col_display as f64,
-                                                );
-                                                
-                                                let props = vec![
-                                                    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                                                        key: PropName::Ident(quote_ident!("name")),
-                                                        value: Box::new(Expr::Lit(Lit::Str(Str { span: DUMMY_SP, value: comp_ident.sym.clone().into(), raw: None, }))),
-                                                    }))),
-                                                    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                                                        key: PropName::Ident(quote_ident!("id")),
-                                                        value: Box::new(Expr::Lit(Lit::Str(Str { span: DUMMY_SP, value: hash.into(), raw: None, }))),
-                                                    }))),
-                                                     PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                                                        key: PropName::Ident(quote_ident!("file")),
-                                                        value: Box::new(Expr::Lit(Lit::Str(Str { span: DUMMY_SP, value: self.file_path.clone().into(), raw: None, }))),
-                                                    }))),
-                                                    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                                                        key: PropName::Ident(quote_ident!("line")),
-                                                        value: Box::new(Expr::Lit(Lit::Num(Number { span: DUMMY_SP, value: linecol.line as f64, raw: None, }))),
-                                                    }))),
-                                                    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                                                        key: PropName::Ident(quote_ident!("offset")),
-                                                        value: Box::new(Expr::Lit(Lit::Num(Number { span: DUMMY_SP, value: linecol.col_display as f64, raw: None, }))),
-                                                    }))),
-                                                ];
-
-                                                let meta_obj = Expr::Object(ObjectLit {
-                                                    span: DUMMY_SP,
-                                                    props,
-                                                });
-    
-                                                Stmt::Decl(Decl::Var(Box::new(VarDecl {
-                                                    span: DUMMY_SP,
-                                                    kind: VarDeclKind::Const,
-                                                    declare: false,
-                                                    decls: vec![VarDeclarator {
-                                                        span: DUMMY_SP,
-                                                        name: Pat::Ident(quote_ident!("h").into()),
-                                                        init: Some(Box::new(Expr::Call(CallExpr {
-                                                            span: DUMMY_SP,
-                                                            callee: quote_ident!("useJitterScope").as_callee(),
-                                                            args: vec![meta_obj.as_arg()],
-                                                            type_args: None,
-                                                            ctxt: SyntaxContext::empty(),
-                                                        }))),
-                                                        definite: false,
-                                                    }],
-                                                    ctxt: SyntaxContext::empty(),
-                                                })))
-                                            };
-    
-                                            arrow.body = Box::new(match &mut *arrow.body {
-                                                BlockStmtOrExpr::BlockStmt(block) => {
-                                                    block.visit_mut_with(self);
-                                                    let mut inner_block = block.clone();
-                                                    inner_block.stmts.insert(0, h_decl_stmt);
-                                                    BlockStmtOrExpr::BlockStmt(inner_block)
-                                                }
-                                                BlockStmtOrExpr::Expr(expr) => {
-                                                    expr.visit_mut_with(self);
-                                                    BlockStmtOrExpr::BlockStmt(BlockStmt {
-                                                        span: expr.span(),
-                                                        stmts: vec![
-                                                            h_decl_stmt,
-                                                            Stmt::Return(ReturnStmt {
-                                                                span: expr.span(),
-                                                                arg: Some(self.wrap_in_h_re(expr.clone())),
-                                                            }),
-                                                        ],
-                                                        ctxt: SyntaxContext::empty(),
-                                                    })
-                                                }
-                                            });
-                                        },
-                                        Expr::Fn(fn_expr) => {
-                                            if let Some(body) = &mut fn_expr.function.body {
-                                                self.instrument_function_body(body, &comp_ident, fn_expr.function.span);
-                                            }
-                                            fn_expr.function.visit_mut_children_with(self);
-                                        },
-                                        _ => {
-                                             init_expr.visit_mut_children_with(self);
-                                        }
-                                    }
-                                     self.current_component = prev_component;
+                                let already_instrumented = match &**init_expr {
+                                    Expr::Arrow(arrow) => matches!(
+                                        &*arrow.body,
+                                        BlockStmtOrExpr::BlockStmt(block) if self.runtime.is_instrumented(block)
+                                    ),
+                                    Expr::Fn(fn_expr) => self.runtime.is_function_instrumented(&fn_expr.function),
+                                    _ => false,
+                                };
+                                if !already_instrumented && renders_or_calls_hooks(init_expr) {
+                                    self.instrument_component_init(&comp_ident, init_expr, is_hook);
                                 }
                             }
                         }
@@ -541,29 +2274,173 @@ impl VisitMut for JitterTransform {
     }
 
     fn visit_mut_call_expr(&mut self, n: &mut CallExpr) {
+        let Some(component) = self.current_component.as_mut() else {
+            n.visit_mut_children_with(self);
+            return;
+        };
+
+        let prev_effect_fn_depth = component.effect_fn_depth;
+        let prev_map_callback = component.map_callback.clone();
+
+        let is_unguarded_effect = n.args.len() == 1
+            && matches!(&n.callee, Callee::Expr(callee) if matches!(
+                &**callee,
+                Expr::Ident(id) if id.sym == *"useEffect" || id.sym == *"useLayoutEffect"
+            ));
+        if self.detect_render_loops && is_unguarded_effect {
+            component.effect_fn_depth = Some(component.nested_fn_depth + 1);
+        }
+        if self.track_keys {
+            if let Some(index) = map_callback(n) {
+                component.map_callback = Some(MapCallback {
+                    fn_depth: component.nested_fn_depth + 1,
+                    index,
+                });
+            }
+        }
+
+        n.visit_mut_children_with(self);
+
+        if let Some(component) = self.current_component.as_mut() {
+            component.effect_fn_depth = prev_effect_fn_depth;
+            component.map_callback = prev_map_callback;
+        }
+    }
+
+    fn visit_mut_jsx_opening_element(&mut self, n: &mut JSXOpeningElement) {
+        n.visit_mut_children_with(self);
+
+        if self.track_props && self.current_component.is_some() {
+            self.track_element_props(n);
+        }
+
+        let Some(map_callback) = self
+            .current_component
+            .as_ref()
+            .and_then(|component| component.map_callback.clone().filter(|map| map.fn_depth == component.nested_fn_depth))
+        else {
+            return;
+        };
+
+        for attr in n.attrs.iter_mut() {
+            if let JSXAttrOrSpread::JSXAttr(JSXAttr {
+                name: JSXAttrName::Ident(name),
+                value: Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
+                    expr: JSXExpr::Expr(key),
+                    ..
+                })),
+                ..
+            }) = attr
+            {
+                if name.sym == *"key" {
+                    *key = self.track_key(key.take(), &map_callback);
+                }
+            }
+        }
+    }
+
+    fn visit_mut_var_declarator(&mut self, n: &mut VarDeclarator) {
+        // Remember `const [state, setState] = useState(...)` setters before the call is wrapped.
+        if self.detect_render_loops {
+            if let (Some(component), Pat::Array(ArrayPat { elems, .. }), Some(init)) =
+                (self.current_component.as_mut(), &n.name, &n.init)
+            {
+                if let (Some(Some(Pat::Ident(setter))), Expr::Call(CallExpr { callee: Callee::Expr(callee), .. })) =
+                    (elems.get(1), &**init)
+                {
+                    if matches!(&**callee, Expr::Ident(id) if id.sym == *"useState" || id.sym == *"useReducer") {
+                        component.setters.insert(setter.id.sym.to_string());
+                    }
+                }
+            }
+        }
+
+        // `const Item = () => ...` in a component body is a new component type on every render.
+        // One declared in the render body itself that renders JSX or calls hooks gets its own
+        // scope, marked `nestedIn` the outer one, so its remounts are reported. Helpers in
+        // callbacks and effects never run as components and only get the warning.
+        if let (Some(outer), Pat::Ident(binding), Some(init)) = (&self.current_component, &n.name, &mut n.init) {
+            let is_component = binding.id.sym.chars().next().is_some_and(|c| c.is_uppercase());
+            if !outer.is_hook && is_component && matches!(&**init, Expr::Arrow(_) | Expr::Fn(_)) {
+                let already_instrumented = match &**init {
+                    Expr::Arrow(arrow) => matches!(
+                        &*arrow.body,
+                        BlockStmtOrExpr::BlockStmt(block) if self.runtime.is_instrumented(block)
+                    ),
+                    Expr::Fn(fn_expr) => self.runtime.is_function_instrumented(&fn_expr.function),
+                    _ => true,
+                };
+                if outer.nested_fn_depth == 0 && !already_instrumented && renders_or_calls_hooks(init) {
+                    let ident = binding.id.clone();
+                    self.instrument_component_init(&ident, init, false);
+                    return;
+                }
+                warn_nested_component(&binding.id, &outer.name);
+            }
+        }
+
         n.visit_mut_children_with(self);
+
+        // Tag `const SomeContext = createContext(...)` with its binding name and location.
+        if let (Pat::Ident(binding_ident), Some(init)) = (&n.name, &mut n.init) {
+            if matches!(&**init, Expr::Call(call) if is_create_context_call(call)) {
+                *init = self.tag_context(init.take(), &binding_ident.id);
+            }
+        }
     }
 
     fn visit_mut_expr(&mut self, expr: &mut Expr) {
-        expr.visit_mut_children_with(self);
+        // `(h.s(id), h.e(useX(), meta))` left by an earlier run wraps the hook already,
+        // and so does its gated form `_jitterEnabled ? (...) : useX()`
+        if is_h_call(expr, "e")
+            || matches!(expr, Expr::Seq(seq) if seq.exprs.first().is_some_and(|first| is_h_call(first, "s")))
+            || matches!(expr, Expr::Cond(cond) if matches!(&*cond.test, Expr::Ident(id) if id.sym == *GATE_IDENT))
+        {
+            return;
+        }
+
+        let is_guard = matches!(
+            expr,
+            Expr::Cond(_)
+                | Expr::Bin(BinExpr {
+                    op: BinaryOp::LogicalAnd | BinaryOp::LogicalOr | BinaryOp::NullishCoalescing,
+                    ..
+                })
+        );
+        if let Some(component) = self.current_component.as_mut().filter(|_| is_guard) {
+            component.guard_depth += 1;
+            expr.visit_mut_children_with(self);
+            if let Some(component) = self.current_component.as_mut() {
+                component.guard_depth -= 1;
+            }
+        } else {
+            expr.visit_mut_children_with(self);
+        }
+
+        if let Expr::Call(call) = expr {
+            if self.current_component.is_some() {
+                self.check_state_initializer(call);
+            }
+        }
+
+        if let Some(tagged) = self.tag_setter_call(expr) {
+            *expr = tagged;
+            return;
+        }
+
         if self.current_component.is_some() {
             if let Expr::Call(call) = &*expr {
                 if let Callee::Expr(callee_expr) = &call.callee {
                     if let Expr::Ident(id) = &**callee_expr {
                         if self.should_wrap_hook(id) {
                             let linecol = self.line_col(call.span);
+                            let hook_id = self.hook_id(&id.sym, call.span);
                             let mut hook_meta_props = vec![
                                     PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
                                         key: PropName::Ident(quote_ident!("id")),
                                         value: Box::new(Expr::Lit(Lit::Str(Str {
                                             span: DUMMY_SP,
-                                            value: self
-                                                .generate_location_hash(
-                                                    &self.file_path,
-                                                    linecol.line as f64,
-                                                    linecol.col_display as f64,
-                                                )
-                                                .into(),
+                                            value: hook_id.as_str().into(),
                                             raw: None,
                                         }))),
                                     }))),
@@ -600,62 +2477,73 @@ impl VisitMut for JitterTransform {
                                         }))),
                                     }))),
                                 ];
-                                hook_meta_props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                                    key: PropName::Ident(quote_ident!("isMocked")),
-                                    value: Box::new(Expr::Call(CallExpr {
-                                        span: DUMMY_SP,
-                                        callee: MemberExpr {
-                                            span: DUMMY_SP,
-                                            obj: Box::new(Expr::Ident(quote_ident!("h").into())),
-                                            prop: MemberProp::Ident(quote_ident!("m")),
-                                        }
-                                        .as_callee(),
-                                        args: vec![id.clone().as_arg()],
-                                        type_args: None,
-                                        ctxt: SyntaxContext::empty(),
-                                    })),
-                                }))));
-
-                            if self.include_arguments {
-                                let mut args_vec = Vec::new();
-                                for arg in &call.args {
-                                    if arg.spread.is_some() {
-                                        args_vec.push(Some(ExprOrSpread {
-                                            spread: None,
-                                            expr: Box::new(Expr::Lit(Lit::Str(Str {
-                                                span: DUMMY_SP,
-                                                value: "...".into(),
-                                                raw: None,
-                                            }))),
-                                        }));
-                                        continue;
+                            // Pass the context object itself so the runtime can group changes by it.
+                            if id.sym == *"useContext" || id.sym == *"use" {
+                                if let Some(context_arg) = call.args.first() {
+                                    if context_arg.spread.is_none() && is_static_reference(&context_arg.expr) {
+                                        hook_meta_props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                                            key: PropName::Ident(quote_ident!("context")),
+                                            value: context_arg.expr.clone(),
+                                        }))));
                                     }
+                                }
+                            }
+
+                            if let Some(capture) = &self.argument_capture {
+                                if capture.includes_hook(id.sym.as_ref()) {
+                                    let args_vec = call
+                                        .args
+                                        .iter()
+                                        .map(|arg| Some(self.capture_argument(capture, arg).as_arg()))
+                                        .collect();
 
-                                    let arg_str = self.cm.span_to_snippet(arg.expr.span()).unwrap_or_else(|_| "<unsupported>".to_string());
-                                    
-                                    args_vec.push(Some(ExprOrSpread {
-                                        spread: None,
-                                        expr: Box::new(Expr::Lit(Lit::Str(Str {
+                                    hook_meta_props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                                        key: PropName::Ident(quote_ident!("arguments")),
+                                        value: Box::new(Expr::Array(ArrayLit {
                                             span: DUMMY_SP,
-                                            value: arg_str.into(),
-                                            raw: None,
-                                        }))),
-                                    }));
+                                            elems: args_vec,
+                                        })),
+                                    }))));
                                 }
+                            }
 
-                                hook_meta_props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                                    key: PropName::Ident(quote_ident!("arguments")),
-                                    value: Box::new(Expr::Array(ArrayLit {
-                                        span: DUMMY_SP,
-                                        elems: args_vec,
-                                    })),
-                                }))));
+                            if let Some(record_index) = self.current_component.as_ref().and_then(|c| c.record_index) {
+                                self.scope_records[record_index].hooks.push(HookRecord {
+                                    id: hook_id.clone(),
+                                    hook: id.sym.to_string(),
+                                    line: linecol.line,
+                                });
                             }
 
-                            let hook_meta = Expr::Object(ObjectLit {
-                                span: DUMMY_SP,
-                                props: hook_meta_props,
-                            });
+                            if self.compact_metadata {
+                                let (inline, manifest) =
+                                    split_props(hook_meta_props, &["id", "context", "arguments"]);
+                                self.manifest.hooks.push((hook_id.clone(), manifest));
+                                hook_meta_props = inline;
+                            }
+
+                            // Context objects and live argument values are read on every render,
+                            // the rest is hoisted. The hook itself is passed so the runtime can
+                            // tell whether it is mocked.
+                            let (dynamic_props, static_props): (Vec<_>, Vec<_>) =
+                                hook_meta_props.into_iter().partition(is_dynamic_meta);
+                            let hook_meta = self.hoist(
+                                "Hook",
+                                Expr::Object(ObjectLit {
+                                    span: DUMMY_SP,
+                                    props: static_props,
+                                }),
+                            );
+                            let mut e_args = vec![expr.clone().as_arg(), hook_meta.as_arg(), id.clone().as_arg()];
+                            if !dynamic_props.is_empty() {
+                                e_args.push(
+                                    Expr::Object(ObjectLit {
+                                        span: DUMMY_SP,
+                                        props: dynamic_props,
+                                    })
+                                    .as_arg(),
+                                );
+                            }
                             let h_ident = quote_ident!("h");
                             let seq_expr = Expr::Seq(SeqExpr {
                                 span: call.span,
@@ -670,13 +2558,7 @@ impl VisitMut for JitterTransform {
                                         .as_callee(),
                                         args: vec![Expr::Lit(Lit::Str(Str {
                                             span: DUMMY_SP,
-                                            value: self
-                                                .generate_location_hash(
-                                                    &self.file_path,
-                                                    linecol.line as f64,
-                                                    linecol.col_display as f64,
-                                                )
-                                                .into(),
+                                            value: hook_id.into(),
                                             raw: None,
                                         }))
                                         .as_arg()],
@@ -691,58 +2573,96 @@ impl VisitMut for JitterTransform {
                                             prop: MemberProp::Ident(quote_ident!("e")),
                                         }
                                         .as_callee(),
-                                        args: vec![expr.clone().as_arg(), hook_meta.as_arg()],
+                                        args: e_args,
                                         type_args: None,
                                         ctxt: SyntaxContext::empty(),
                                     })),
                                 ],
                             });
-                            *expr = Expr::Paren(ParenExpr {
+                            let wrapped = Expr::Paren(ParenExpr {
                                 span: call.span,
                                 expr: Box::new(seq_expr),
                             });
+                            *expr = self.gated(wrapped, expr.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit_mut_function(&mut self, n: &mut Function) {
+        if let Some(component) = self.current_component.as_mut() {
+            component.nested_fn_depth += 1;
+        }
+        n.visit_mut_children_with(self);
+        if let Some(component) = self.current_component.as_mut() {
+            component.nested_fn_depth -= 1;
+        }
+    }
+
+    fn visit_mut_arrow_expr(&mut self, n: &mut ArrowExpr) {
+        if let Some(component) = self.current_component.as_mut() {
+            component.nested_fn_depth += 1;
+        }
+        n.visit_mut_children_with(self);
+        if let Some(component) = self.current_component.as_mut() {
+            component.nested_fn_depth -= 1;
+        }
+    }
+
+    fn visit_mut_if_stmt(&mut self, n: &mut IfStmt) {
+        // Remember the guarding condition of returns placed directly in either branch.
+        if self.current_component.is_some() {
+            if let Some(condition) = self.short_snippet(n.test.span(), 40) {
+                for (branch, condition) in [
+                    (Some(&n.cons), condition.clone()),
+                    (n.alt.as_ref(), format!("!({condition})")),
+                ] {
+                    let stmts = match branch.map(|stmt| &**stmt) {
+                        Some(Stmt::Block(block)) => block.stmts.iter().collect(),
+                        Some(stmt) => vec![stmt],
+                        None => vec![],
+                    };
+                    for stmt in stmts {
+                        if let Stmt::Return(ret) = stmt {
+                            self.branch_conditions.insert(ret.span.lo(), condition.clone());
                         }
                     }
                 }
             }
         }
+
+        if let Some(component) = self.current_component.as_mut() {
+            component.guard_depth += 1;
+            n.visit_mut_children_with(self);
+            if let Some(component) = self.current_component.as_mut() {
+                component.guard_depth -= 1;
+            }
+        } else {
+            n.visit_mut_children_with(self);
+        }
     }
 
     fn visit_mut_return_stmt(&mut self, n: &mut ReturnStmt) {
         // First recurse.
         n.visit_mut_children_with(self);
 
-        // Only touch code while we're inside a component.
-        if self.current_component.is_none() {
+        // Only touch code while we're inside a component; hooks do not render.
+        // Returns of callbacks and effect cleanups are not what the component renders.
+        if self
+            .current_component
+            .as_ref()
+            .is_none_or(|c| c.is_hook || c.nested_fn_depth > 0)
+        {
             return;
         }
 
         // Only wrap `return <expr>;`
         if let Some(arg) = &mut n.arg {
             // Avoid double‑wrapping.
-            let already_wrapped = if let Expr::Call(CallExpr {
-                callee: Callee::Expr(callee_expr),
-                ..
-            }) = &**arg {
-                if let Expr::Member(MemberExpr {
-                    obj,
-                    prop: MemberProp::Ident(prop_ident),
-                    ..
-                }) = &**callee_expr {
-                    if let Expr::Ident(obj_ident) = &**obj {
-                        obj_ident.sym == *"h" && prop_ident.sym == *"re"
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                }
-            } else {
-                false
-            };
-            
-            if !already_wrapped {
-                *arg = self.wrap_in_h_re(arg.clone());
+            if !is_h_call(arg, "re") {
+                *arg = self.wrap_in_h_re(arg.take(), n.span);
             }
         }
     }
@@ -814,7 +2734,7 @@ pub fn process_transform(
     let cm: PluginSourceMapProxy = metadata.source_map.clone();
 
     let options = match config {
-        Config::WithOptions(opts) => opts,
+        Config::WithOptions(opts) => *opts,
         _ => Options::default(),
     };
 
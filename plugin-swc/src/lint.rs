@@ -0,0 +1,260 @@
+use std::collections::HashSet;
+use swc_core::common::{Span, Spanned};
+use swc_core::ecma::ast::*;
+use swc_core::ecma::visit::{Visit, VisitWith};
+
+/// Hooks whose last argument is a dependency array.
+const DEPENDENCY_HOOKS: [&str; 6] = [
+    "useEffect",
+    "useLayoutEffect",
+    "useInsertionEffect",
+    "useMemo",
+    "useCallback",
+    "useImperativeHandle",
+];
+
+/// A reference that is recreated on every render, found without running the code.
+pub(crate) struct LintFinding {
+    pub rule: &'static str,
+    /// The parameter, prop or hook the reference is passed to.
+    pub name: String,
+    pub span: Span,
+}
+
+impl LintFinding {
+    pub fn message(&self) -> String {
+        match self.rule {
+            "default-param" => format!(
+                "react-jitter: default value of `{}` creates a new reference on every render",
+                self.name
+            ),
+            "memo-prop" => format!(
+                "react-jitter: inline `{}` prop defeats memoization of the receiving component",
+                self.name
+            ),
+            "provider-value" => format!(
+                "react-jitter: inline `value` on `{}` re-renders every consumer on each render",
+                self.name
+            ),
+            _ => format!(
+                "react-jitter: `{}` dependency array contains a value recreated on every render",
+                self.name
+            ),
+        }
+    }
+}
+
+/// Runs the static checks over a module before it is instrumented.
+pub(crate) fn lint_module(module: &Module) -> Vec<LintFinding> {
+    let mut memo_components = MemoComponents::default();
+    module.visit_with(&mut memo_components);
+
+    let mut linter = Linter {
+        memo_components: memo_components.names,
+        findings: vec![],
+    };
+    module.visit_with(&mut linter);
+    linter.findings
+}
+
+fn is_new_reference(expr: &Expr) -> bool {
+    match expr {
+        Expr::Object(_) | Expr::Array(_) | Expr::Arrow(_) | Expr::Fn(_) | Expr::Class(_) => true,
+        Expr::Paren(paren) => is_new_reference(&paren.expr),
+        _ => false,
+    }
+}
+
+fn is_component_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_uppercase())
+}
+
+fn is_memo_call(expr: &Expr) -> bool {
+    let Expr::Call(CallExpr {
+        callee: Callee::Expr(callee),
+        ..
+    }) = expr
+    else {
+        return false;
+    };
+
+    match &**callee {
+        Expr::Ident(ident) => ident.sym == *"memo",
+        Expr::Member(MemberExpr {
+            obj,
+            prop: MemberProp::Ident(prop),
+            ..
+        }) => prop.sym == *"memo" && matches!(&**obj, Expr::Ident(obj) if obj.sym == *"React"),
+        _ => false,
+    }
+}
+
+fn jsx_object_name(obj: &JSXObject) -> String {
+    match obj {
+        JSXObject::Ident(ident) => ident.sym.to_string(),
+        JSXObject::JSXMemberExpr(member) => format!("{}.{}", jsx_object_name(&member.obj), member.prop.sym),
+    }
+}
+
+fn jsx_element_name(name: &JSXElementName) -> Option<String> {
+    match name {
+        JSXElementName::Ident(ident) => Some(ident.sym.to_string()),
+        JSXElementName::JSXMemberExpr(member) => {
+            Some(format!("{}.{}", jsx_object_name(&member.obj), member.prop.sym))
+        }
+        JSXElementName::JSXNamespacedName(_) => None,
+    }
+}
+
+/// Collects `const Row = memo(...)` bindings declared in the module.
+#[derive(Default)]
+struct MemoComponents {
+    names: HashSet<String>,
+}
+
+impl Visit for MemoComponents {
+    fn visit_var_declarator(&mut self, n: &VarDeclarator) {
+        if let (Pat::Ident(binding), Some(init)) = (&n.name, &n.init) {
+            if is_memo_call(init) {
+                self.names.insert(binding.id.sym.to_string());
+            }
+        }
+        n.visit_children_with(self);
+    }
+}
+
+struct Linter {
+    memo_components: HashSet<String>,
+    findings: Vec<LintFinding>,
+}
+
+impl Linter {
+    fn check_params<'a>(&mut self, params: impl Iterator<Item = &'a Pat>) {
+        for param in params {
+            self.check_pattern(param);
+        }
+    }
+
+    fn check_pattern(&mut self, pat: &Pat) {
+        match pat {
+            Pat::Assign(assign) => {
+                if is_new_reference(&assign.right) {
+                    let name = match &*assign.left {
+                        Pat::Ident(binding) => binding.id.sym.to_string(),
+                        _ => "props".into(),
+                    };
+                    self.report("default-param", name, assign.right.span());
+                }
+                self.check_pattern(&assign.left);
+            }
+            Pat::Object(object) => {
+                for prop in &object.props {
+                    match prop {
+                        ObjectPatProp::Assign(assign) => {
+                            if let Some(value) = &assign.value {
+                                if is_new_reference(value) {
+                                    self.report("default-param", assign.key.sym.to_string(), value.span());
+                                }
+                            }
+                        }
+                        ObjectPatProp::KeyValue(kv) => self.check_pattern(&kv.value),
+                        ObjectPatProp::Rest(_) => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn report(&mut self, rule: &'static str, name: String, span: Span) {
+        self.findings.push(LintFinding { rule, name, span });
+    }
+}
+
+impl Visit for Linter {
+    fn visit_fn_decl(&mut self, n: &FnDecl) {
+        if is_component_name(n.ident.sym.as_ref()) {
+            self.check_params(n.function.params.iter().map(|p| &p.pat));
+        }
+        n.visit_children_with(self);
+    }
+
+    fn visit_var_declarator(&mut self, n: &VarDeclarator) {
+        if let (Pat::Ident(binding), Some(init)) = (&n.name, &n.init) {
+            if is_component_name(binding.id.sym.as_ref()) {
+                match &**init {
+                    Expr::Arrow(arrow) => self.check_params(arrow.params.iter()),
+                    Expr::Fn(fn_expr) => {
+                        self.check_params(fn_expr.function.params.iter().map(|p| &p.pat))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        n.visit_children_with(self);
+    }
+
+    fn visit_export_default_decl(&mut self, n: &ExportDefaultDecl) {
+        if let DefaultDecl::Fn(fn_expr) = &n.decl {
+            let is_component = fn_expr
+                .ident
+                .as_ref()
+                .is_none_or(|ident| is_component_name(ident.sym.as_ref()));
+            if is_component {
+                self.check_params(fn_expr.function.params.iter().map(|p| &p.pat));
+            }
+        }
+        n.visit_children_with(self);
+    }
+
+    fn visit_jsx_opening_element(&mut self, n: &JSXOpeningElement) {
+        if let Some(element) = jsx_element_name(&n.name) {
+            let is_provider = element.ends_with(".Provider");
+            let is_memo = self.memo_components.contains(&element);
+
+            for attr in &n.attrs {
+                let JSXAttrOrSpread::JSXAttr(JSXAttr {
+                    name: JSXAttrName::Ident(prop),
+                    value: Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
+                        expr: JSXExpr::Expr(value),
+                        ..
+                    })),
+                    ..
+                }) = attr
+                else {
+                    continue;
+                };
+
+                if !is_new_reference(value) {
+                    continue;
+                }
+
+                if is_provider && prop.sym == *"value" {
+                    self.report("provider-value", element.clone(), value.span());
+                } else if is_memo {
+                    self.report("memo-prop", prop.sym.to_string(), value.span());
+                }
+            }
+        }
+        n.visit_children_with(self);
+    }
+
+    fn visit_call_expr(&mut self, n: &CallExpr) {
+        if let Callee::Expr(callee) = &n.callee {
+            if let Expr::Ident(hook) = &**callee {
+                if DEPENDENCY_HOOKS.contains(&hook.sym.as_ref()) && n.args.len() > 1 {
+                    if let Some(ExprOrSpread { spread: None, expr }) = n.args.last() {
+                        if let Expr::Array(deps) = &**expr {
+                            for dep in deps.elems.iter().flatten() {
+                                if dep.spread.is_none() && is_new_reference(&dep.expr) {
+                                    self.report("dependency", hook.sym.to_string(), dep.expr.span());
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        n.visit_children_with(self);
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::{HashMap, HashSet};
+use swc_core::common::errors::HANDLER;
+use swc_core::common::util::take::Take;
+use swc_core::ecma::ast::*;
+use swc_core::ecma::visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+
+use super::{is_instrumented_module, Runtime, GATE_IDENT, HOISTED_PREFIX, MARKER_IDENT};
+
+/// Scope methods that return their first argument.
+const PASS_THROUGH_METHODS: [&str; 6] = ["e", "re", "o", "k", "p", "i"];
+
+/// Scope methods that are only called for their side effects.
+const SIDE_EFFECT_METHODS: [&str; 4] = ["s", "l", "a", "f"];
+
+/// Runtime exports that are only called for their side effects.
+const SIDE_EFFECT_EXPORTS: [&str; 3] = ["reactJitter", "registerScopes", "registerManifest"];
+
+/// Removes the runtime import of a module together with every instrumentation
+/// call, whether written by hand or left by an earlier run of the plugin.
+/// Runtime exports that cannot be removed are reported as errors.
+pub(crate) fn strip_module(module: &mut Module, runtime: &Runtime) {
+    let mut stripper = Stripper {
+        runtime,
+        instrumented: is_instrumented_module(module),
+        imports: HashMap::new(),
+        scopes: HashSet::new(),
+    };
+    module.body.retain(|item| {
+        if let Some(pattern) = item.as_stmt().and_then(|stmt| runtime.require_pattern(stmt)) {
+            for prop in &pattern.props {
+                match prop {
+                    ObjectPatProp::Assign(assign) => {
+                        let name = assign.key.id.sym.to_string();
+                        stripper.imports.insert(name.clone(), name);
+                    }
+                    ObjectPatProp::KeyValue(KeyValuePatProp {
+                        key: PropName::Ident(key),
+                        value,
+                    }) => {
+                        if let Pat::Ident(local) = &**value {
+                            stripper.imports.insert(local.id.sym.to_string(), key.sym.to_string());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            return false;
+        }
+
+        let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item else {
+            return true;
+        };
+        if import.src.value != *runtime.module {
+            return true;
+        }
+
+        for specifier in &import.specifiers {
+            let (local, export) = match specifier {
+                ImportSpecifier::Named(named) => {
+                    let export = match &named.imported {
+                        Some(ModuleExportName::Ident(ident)) => ident.sym.to_string(),
+                        Some(ModuleExportName::Str(name)) => name.value.to_atom_lossy().to_string(),
+                        None => named.local.sym.to_string(),
+                    };
+                    (named.local.sym.to_string(), export)
+                }
+                ImportSpecifier::Default(default) => (default.local.sym.to_string(), "default".into()),
+                ImportSpecifier::Namespace(namespace) => (namespace.local.sym.to_string(), "*".into()),
+            };
+            stripper.imports.insert(local, export);
+        }
+        false
+    });
+
+    if stripper.imports.is_empty() {
+        return;
+    }
+
+    module.visit_mut_with(&mut stripper);
+
+    let mut leftovers = Leftovers { stripper: &stripper };
+    module.visit_with(&mut leftovers);
+}
+
+struct Stripper<'a> {
+    runtime: &'a Runtime,
+    /// Whether the plugin instrumented the module, so its `_jitter` constants and
+    /// `gate` are generated code rather than user bindings that happen to match.
+    instrumented: bool,
+    /// Local name of each runtime import, mapped to the export it refers to.
+    imports: HashMap<String, String>,
+    /// Bindings initialized with `useJitterScope(...)`, usually `h`.
+    scopes: HashSet<String>,
+}
+
+impl Stripper<'_> {
+    fn runtime_export(&self, expr: &Expr) -> Option<&str> {
+        match expr {
+            Expr::Ident(ident) => self.imports.get(ident.sym.as_ref()).map(String::as_str),
+            _ => None,
+        }
+    }
+
+    /// `"e"` for `h.e(...)` when `h` is a scope.
+    fn scope_method<'a>(&self, call: &'a CallExpr) -> Option<&'a str> {
+        let Callee::Expr(callee) = &call.callee else {
+            return None;
+        };
+        match &**callee {
+            Expr::Member(MemberExpr {
+                obj,
+                prop: MemberProp::Ident(prop),
+                ..
+            }) if matches!(&**obj, Expr::Ident(obj) if self.scopes.contains(obj.sym.as_ref())) => {
+                Some(prop.sym.as_ref())
+            }
+            _ => None,
+        }
+    }
+
+    fn callee_export(&self, call: &CallExpr) -> Option<&str> {
+        match &call.callee {
+            Callee::Expr(callee) => self.runtime_export(callee),
+            _ => None,
+        }
+    }
+
+    fn is_side_effect(&self, expr: &Expr) -> bool {
+        let Expr::Call(call) = expr else {
+            return false;
+        };
+        self.scope_method(call).is_some_and(|method| SIDE_EFFECT_METHODS.contains(&method))
+            || self.callee_export(call).is_some_and(|export| SIDE_EFFECT_EXPORTS.contains(&export))
+    }
+
+    /// `useJitterScope(...)`, or its gated form `_jitterEnabled ? useJitterScope(...) : disabledScope`.
+    fn is_scope_init(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Call(call) => self.callee_export(call) == Some(self.runtime.export.as_str()),
+            Expr::Paren(ParenExpr { expr, .. }) | Expr::Cond(CondExpr { cons: expr, .. }) => self.is_scope_init(expr),
+            _ => false,
+        }
+    }
+
+    /// `try { ... } catch (error) { h.a(error); throw error; } finally { h.f(); }`, the
+    /// guard `reportAbortedRenders` puts around a component body.
+    fn is_guard(&self, stmt: &Stmt) -> bool {
+        let Stmt::Try(guard) = stmt else {
+            return false;
+        };
+        let is_scope_call = |stmt: &Stmt, method: &str| {
+            matches!(stmt, Stmt::Expr(ExprStmt { expr, .. })
+                if matches!(&**expr, Expr::Call(call) if self.scope_method(call) == Some(method)))
+        };
+        let reports = match &guard.handler {
+            Some(CatchClause {
+                param: Some(Pat::Ident(param)),
+                body,
+                ..
+            }) => matches!(body.stmts.as_slice(), [report, Stmt::Throw(ThrowStmt { arg, .. })]
+                if is_scope_call(report, "a") && matches!(&**arg, Expr::Ident(id) if id.sym == param.id.sym)),
+            _ => false,
+        };
+        let finishes = guard
+            .finalizer
+            .as_ref()
+            .is_some_and(|finalizer| matches!(finalizer.stmts.as_slice(), [finish] if is_scope_call(finish, "f")));
+        reports && finishes
+    }
+
+    fn is_removed_stmt(&self, stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::Expr(ExprStmt { expr, .. }) => self.is_side_effect(expr),
+            Stmt::Decl(Decl::Var(var)) => var.decls.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+impl VisitMut for Stripper<'_> {
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        items.visit_mut_children_with(self);
+        items.retain(|item| !matches!(item, ModuleItem::Stmt(stmt) if self.is_removed_stmt(stmt)));
+    }
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        // Guards are recognized by their scope calls, before those are removed
+        let mut unwrapped_guard = false;
+        for stmt in stmts.iter_mut() {
+            let is_guard = self.is_guard(stmt);
+            stmt.visit_mut_with(self);
+            if let (true, Stmt::Try(guard)) = (is_guard, &mut *stmt) {
+                *stmt = Stmt::Block(guard.block.take());
+                unwrapped_guard = true;
+            }
+        }
+        stmts.retain(|stmt| !self.is_removed_stmt(stmt));
+
+        // The guard of `reportAbortedRenders` is the whole body after the scope declaration
+        if unwrapped_guard {
+            if let [Stmt::Block(block)] = stmts.as_mut_slice() {
+                *stmts = block.stmts.take();
+            }
+        }
+    }
+
+    fn visit_mut_try_stmt(&mut self, n: &mut TryStmt) {
+        n.visit_mut_children_with(self);
+        // `finally { h.f(); }` is left empty
+        if n.handler.is_some() && n.finalizer.as_ref().is_some_and(|finalizer| finalizer.stmts.is_empty()) {
+            n.finalizer = None;
+        }
+    }
+
+    fn visit_mut_var_decl(&mut self, n: &mut VarDecl) {
+        // Scope declarations, and the marker and hoisted `_jitter` constants of instrumented modules
+        let mut decls = Vec::with_capacity(n.decls.len());
+        for decl in n.decls.take() {
+            let Pat::Ident(binding) = &decl.name else {
+                decls.push(decl);
+                continue;
+            };
+            if decl.init.as_deref().is_some_and(|init| self.is_scope_init(init)) {
+                self.scopes.insert(binding.id.sym.to_string());
+            } else if !(self.instrumented
+                && (binding.id.sym == *MARKER_IDENT || binding.id.sym.starts_with(HOISTED_PREFIX)))
+            {
+                decls.push(decl);
+            }
+        }
+        n.decls = decls;
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        expr.visit_mut_children_with(self);
+
+        match expr {
+            Expr::Call(call) => {
+                let is_pass_through = self
+                    .scope_method(call)
+                    .is_some_and(|method| PASS_THROUGH_METHODS.contains(&method))
+                    || self.callee_export(call) == Some("tagContext");
+                if is_pass_through {
+                    if let Some(ExprOrSpread { spread: None, expr: value }) = call.args.first_mut() {
+                        *expr = *value.take();
+                    }
+                }
+            }
+            Expr::Seq(seq) => {
+                // The last expression is the value of the sequence
+                let value = seq.exprs.pop();
+                seq.exprs.retain(|item| !self.is_side_effect(item));
+                seq.exprs.extend(value);
+                if seq.exprs.len() == 1 {
+                    *expr = *seq.exprs.remove(0);
+                }
+            }
+            Expr::Cond(cond)
+                if self.instrumented && matches!(&*cond.test, Expr::Ident(test) if test.sym == *GATE_IDENT) =>
+            {
+                *expr = *cond.alt.take();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reports runtime imports and scopes that are still referenced once stripped.
+struct Leftovers<'a> {
+    stripper: &'a Stripper<'a>,
+}
+
+impl Visit for Leftovers<'_> {
+    fn visit_ident(&mut self, n: &Ident) {
+        let name = n.sym.as_ref();
+        let message = if let Some(export) = self.stripper.imports.get(name) {
+            format!(
+                "react-jitter: `{export}` from {} cannot be stripped for production",
+                self.stripper.runtime.module
+            )
+        } else if self.stripper.scopes.contains(name) {
+            format!("react-jitter: unknown use of the `{name}` jitter scope cannot be stripped for production")
+        } else {
+            return;
+        };
+        HANDLER.with(|handler| handler.struct_span_err(n.span, &message).emit());
+    }
+}
@@ -2,17 +2,55 @@ import type {
   HookChange,
   HookEndEvent,
   HookAddress,
+  InitializerSite,
+  DiscardedInitializer,
+  KeyChurn,
+  KeySite,
+  PropSite,
   ReactJitterOptions,
+  CoverageReport,
+  RenderAbort,
+  RenderCause,
+  RenderLoop,
+  RenderLoopSite,
+  UnstableProp,
+  ReturnBranch,
   Scope,
   Comparator,
 } from './types';
 
 import React from 'react';
 import { compareChanges } from './utils/compareChanges';
+import { getContextMeta } from './utils/contextMeta';
+import { getKeyChurn, recordKey } from './utils/keys';
+import { resolveHook, resolveScope } from './utils/manifest';
+import type { KeySiteState } from './utils/keys';
+import { getUnstableProps, recordProp } from './utils/props';
+import type { PropSiteState } from './utils/props';
+import {
+  clearCoverage,
+  getCoverage,
+  markHookCalled,
+  markScopeRendered,
+} from './utils/coverage';
+import {
+  DEFAULT_RENDER_LOOP_THRESHOLD,
+  clearRenderLoops,
+  countSetterCall,
+  getRenderLoopMessage,
+} from './utils/renderLoop';
+import { clearRemounts, isRemount, recordRender } from './utils/remounts';
+import { markRenderStart, measureRender, now } from './utils/timing';
+
+export { tagContext } from './utils/contextMeta';
+export { registerScopes, getCoverage } from './utils/coverage';
+export { registerManifest, getManifest } from './utils/manifest';
 
 type HookCall = HookChange &
   HookEndEvent & {
     scope: Scope;
+    // Component and custom hooks that led to this hook, e.g. CartPage > useCart > useSelector
+    path: string[];
     previousResult: unknown;
     currentResult: unknown;
   };
@@ -24,9 +62,30 @@ const scopes: Record<
     renderCount: number;
     scopeId: string;
     hookChanges: HookCall[];
+    branch?: ReturnBranch;
+    branchCounts: Record<number, number>;
+    branchFlips: number;
+    renderStart?: number;
+    renderDuration?: number;
+    abortedRenders: number;
+    owner?: string;
+    owners?: { scopeId: string; name: string }[];
+    renderCause?: RenderCause;
+    hookChangesAtRenderStart: number;
+    lastOwnerRenderCount?: number;
+    keySites: Record<string, KeySiteState>;
+    keyChurn?: KeyChurn[];
+    propSites: Record<string, PropSiteState>;
+    unstableProps?: UnstableProp[];
+    path: string[];
+    remountReason?: string;
+    discardedInitializers?: DiscardedInitializer[];
   }
 > = {};
 
+// Carries the scope id of the component that rendered the current subtree
+const OwnerContext = React.createContext<string | null>(null);
+
 const hookStack = new Map<string, unknown>();
 
 const scopeCounter: Record<string, number> = {};
@@ -43,19 +102,74 @@ declare global {
           renderCount: number;
         },
       ) => void;
+      onRenderAbort?: (abort: RenderAbort) => void;
+      getCoverage: () => CoverageReport;
+      onRenderLoop?: (loop: RenderLoop) => void;
+      renderLoopThreshold?: number;
       clear: () => void;
     };
   }
 }
 
+function isMock(value: unknown): boolean {
+  if (typeof value !== 'function') {
+    return false;
+  }
+  return 'mockImplementation' in value || 'mockReturnValue' in value;
+}
+
+type JitterScope = {
+  s: (id: string) => void;
+  e: (
+    hookResult: unknown,
+    hookEndEvent: Pick<HookEndEvent, 'id'> & Partial<HookEndEvent>,
+    hookFn?: unknown,
+    dynamic?: Pick<HookEndEvent, 'context' | 'arguments'>,
+  ) => unknown;
+  re: <T>(renderResult: T, branch?: ReturnBranch) => T;
+  m: (value: unknown) => boolean;
+  a: (error: unknown) => void;
+  f: () => void;
+  o: (children: unknown) => React.ReactElement;
+  l: (site: RenderLoopSite) => void;
+  k: <T>(key: T, site: KeySite) => T;
+  p: <T>(value: T, site: PropSite) => T;
+  i: <T>(value: T, site: InitializerSite) => T;
+};
+
+const identity = <T>(value: T): T => value;
+const noop = () => {};
+
+/**
+ * Used instead of `useJitterScope` when the `gate` configured in the plugin is
+ * off, so instrumented code runs with plain pass-through calls.
+ */
+export const disabledScope: JitterScope = {
+  s: noop,
+  e: identity,
+  re: identity,
+  m: isMock,
+  a: noop,
+  f: noop,
+  o: identity as JitterScope['o'],
+  l: noop,
+  k: identity,
+  p: identity,
+  i: identity,
+};
+
 /**
  * A React hook that creates a jitter scope for measuring component performance.
  * @param options Configuration options for the jitter scope
  * @returns void
  */
-export function useJitterScope(scope: Scope) {
+export function useJitterScope(
+  compactScope: Pick<Scope, 'id'> & Partial<Scope>,
+) {
+  const scope = resolveScope(compactScope);
   const scopeCount = React.useRef(getScopeCount(scope)).current;
   const scopeId = `${scope.id}-${scopeCount}`;
+  const ownerScopeId = React.useContext(OwnerContext);
 
   if (!scopes[scopeId]) {
     scopes[scopeId] = {
@@ -64,17 +178,36 @@ export function useJitterScope(scope: Scope) {
       ...scope,
       hookResults: {},
       hookChanges: [],
+      branchCounts: {},
+      branchFlips: 0,
+      abortedRenders: 0,
+      hookChangesAtRenderStart: 0,
+      keySites: {},
+      propSites: {},
+      path: getScopePath(scope),
     };
+
+    // A component declared inside another one is a new type on every render of its parent
+    if (scope.nestedIn && isRemount(scope)) {
+      scopes[scopeId].remountReason =
+        `${scope.name} was remounted because it is redefined on each render of ${scope.nestedIn}`;
+    }
   }
 
   scopes[scopeId].renderCount++;
+  recordRender(scope, scopeId, scopes[scopeId].renderCount);
+  markScopeRendered(scope.id);
+  scopes[scopeId].owner = ownerScopeId ?? undefined;
+  scopes[scopeId].hookChangesAtRenderStart = scopes[scopeId].hookChanges.length;
 
-  const hooks = React.useRef<{
-    s: (id: string) => void;
-    e: (hookResult: unknown, hookEndEvent: HookEndEvent) => unknown;
-    re: <T>(renderResult: T) => T;
-    m: (value: unknown) => boolean;
-  } | null>(null);
+  if (scope.timing) {
+    scopes[scopeId].renderStart = now();
+    if (scope.timing.marks) {
+      markRenderStart(scopeId);
+    }
+  }
+
+  const hooks = React.useRef<JitterScope | null>(null);
 
   if (!hooks.current) {
     hooks.current = {
@@ -82,13 +215,20 @@ export function useJitterScope(scope: Scope) {
         const hookId = `${scopeId}-${id}`;
         hookStack.set(hookId, null);
       },
-      e: (hookResult: unknown, hookEndEvent: HookEndEvent) => {
+      e: (
+        hookResult: unknown,
+        compactHookEndEvent: Pick<HookEndEvent, 'id'> & Partial<HookEndEvent>,
+        hookFn?: unknown,
+        dynamic?: Pick<HookEndEvent, 'context' | 'arguments'>,
+      ) => {
+        const hookEndEvent = resolveHook(compactHookEndEvent);
         const currentScope = scopes[scopeId];
         if (!currentScope) {
           return hookResult;
         }
 
         const hookId = `${scopeId}-${hookEndEvent.id}`;
+        markHookCalled(hookEndEvent.id);
 
         if (shouldReportChanges()) {
           const prevResult = currentScope.hookResults[hookId];
@@ -107,15 +247,23 @@ export function useJitterScope(scope: Scope) {
               offset: hookEndEvent.offset,
               id: hookEndEvent.id,
               scope,
+              path: [...currentScope.path, hookEndEvent.hook],
               ...changes,
               previousResult: prevResult,
               currentResult: hookResult,
             };
-            if (hookEndEvent.arguments) {
-              hookCall.arguments = hookEndEvent.arguments;
+            const args = dynamic?.arguments ?? hookEndEvent.arguments;
+            if (args) {
+              hookCall.arguments = args;
+            }
+            const isMocked = hookEndEvent.isMocked ?? isMock(hookFn);
+            if (isMocked) {
+              hookCall.isMocked = isMocked;
             }
-            if (hookEndEvent.isMocked) {
-              hookCall.isMocked = hookEndEvent.isMocked;
+            const context = dynamic?.context ?? hookEndEvent.context;
+            if (context) {
+              hookCall.context = context;
+              hookCall.contextName = getContextName(context);
             }
             scopes[scopeId].hookChanges.push(hookCall);
             callOnHookChange(hookCall);
@@ -127,16 +275,148 @@ export function useJitterScope(scope: Scope) {
 
         return hookResult;
       },
-      re: <T>(renderResult: T): T => {
+      re: <T>(renderResult: T, branch?: ReturnBranch): T => {
+        const currentScope = scopes[scopeId];
+        if (branch) {
+          // Count renders per return statement and how often the branch changes
+          if (
+            currentScope.branch &&
+            currentScope.branch.index !== branch.index
+          ) {
+            currentScope.branchFlips++;
+          }
+          currentScope.branch = branch;
+          currentScope.branchCounts[branch.index] =
+            (currentScope.branchCounts[branch.index] ?? 0) + 1;
+        }
+
+        if (scope.timing && currentScope.renderStart !== undefined) {
+          currentScope.renderDuration = now() - currentScope.renderStart;
+          currentScope.renderStart = undefined;
+          if (scope.timing.marks) {
+            measureRender(scope, scopeId);
+          }
+        }
+
+        if (Object.keys(currentScope.keySites).length > 0) {
+          currentScope.keyChurn = getKeyChurn(
+            currentScope.keySites,
+            currentScope.renderCount,
+          );
+        }
+
+        if (Object.keys(currentScope.propSites).length > 0) {
+          currentScope.unstableProps = getUnstableProps(
+            currentScope.propSites,
+            currentScope.renderCount,
+          );
+        }
+
+        if (currentScope.owner !== undefined) {
+          currentScope.owners = getOwnerChain(currentScope.owner);
+          currentScope.renderCause = getRenderCause(currentScope);
+        }
+
         // Render end - call onRender callback with scope data
-        callOnRender(scopes[scopeId]);
+        callOnRender(currentScope);
         return renderResult;
       },
-      m: (value: unknown): boolean => {
-        if (typeof value !== 'function') {
-          return false;
+      m: isMock,
+      a: (error: unknown) => {
+        // Render aborted - the component threw an error or a promise
+        const currentScope = scopes[scopeId];
+        if (!currentScope) {
+          return;
+        }
+
+        currentScope.abortedRenders++;
+        callOnRenderAbort({
+          scope: currentScope,
+          reason: isThenable(error) ? 'suspended' : 'errored',
+          hookId: getPendingHookId(scopeId),
+          error,
+        });
+      },
+      f: () => {
+        // Drop hooks that never reached h.e because the render was aborted
+        for (const hookId of getPendingHookIds(scopeId)) {
+          hookStack.delete(hookId);
+        }
+      },
+      o: (children: unknown) =>
+        // Let child scopes know which scope rendered them
+        React.createElement(
+          OwnerContext.Provider,
+          { value: scopeId },
+          children as React.ReactNode,
+        ),
+      l: (site: RenderLoopSite) => {
+        // A setter is about to run during render or in an effect without dependencies
+        const currentScope = scopes[scopeId];
+        if (!currentScope) {
+          return;
+        }
+
+        const calls = countSetterCall(
+          `${scopeId}-${site.id}`,
+          window?.reactJitter?.renderLoopThreshold ??
+            DEFAULT_RENDER_LOOP_THRESHOLD,
+          now(),
+        );
+        if (calls === undefined) {
+          return;
+        }
+
+        const loop: RenderLoop = { scope: currentScope, site, calls };
+        callOnRenderLoop(loop);
+        // Break out of the loop before the browser locks up
+        throw new Error(getRenderLoopMessage(loop));
+      },
+      k: <T>(key: T, site: KeySite): T => {
+        // Remember the keys of this render to compare with the next one
+        const currentScope = scopes[scopeId];
+        if (currentScope) {
+          recordKey(
+            currentScope.keySites,
+            site,
+            key,
+            currentScope.renderCount,
+          );
+        }
+        return key;
+      },
+      p: <T>(value: T, site: PropSite): T => {
+        // Compare what is passed to child components with the previous render
+        const currentScope = scopes[scopeId];
+        if (currentScope) {
+          recordProp(
+            currentScope.propSites,
+            site,
+            value,
+            currentScope.renderCount,
+          );
+        }
+        return value;
+      },
+      i: <T>(value: T, site: InitializerSite): T => {
+        // Only the first render uses the initial state, later runs are thrown away
+        const currentScope = scopes[scopeId];
+        if (!currentScope || currentScope.renderCount <= 1) {
+          return value;
+        }
+
+        if (!currentScope.discardedInitializers) {
+          currentScope.discardedInitializers = [];
+        }
+        const discarded = currentScope.discardedInitializers.find(
+          (initializer) => initializer.id === site.id,
+        );
+        if (discarded) {
+          discarded.runs++;
+        } else {
+          currentScope.discardedInitializers.push({ ...site, runs: 1 });
         }
-        return 'mockImplementation' in value || 'mockReturnValue' in value;
+        return value;
       },
     };
   }
@@ -157,12 +437,32 @@ export function reactJitter(options: ReactJitterOptions) {
     onHookChange:
       windowGlobal.reactJitter?.onHookChange ?? options.onHookChange,
     onRender: windowGlobal.reactJitter?.onRender ?? options.onRender,
+    onRenderAbort:
+      windowGlobal.reactJitter?.onRenderAbort ?? options.onRenderAbort,
+    getCoverage,
+    onRenderLoop:
+      windowGlobal.reactJitter?.onRenderLoop ?? options.onRenderLoop,
+    renderLoopThreshold:
+      windowGlobal.reactJitter?.renderLoopThreshold ??
+      options.renderLoopThreshold,
     clear: () => {
       Object.keys(scopes).forEach((key) => {
         scopes[key].renderCount = 0;
         scopes[key].hookChanges = [];
         scopes[key].hookResults = {};
+        scopes[key].branch = undefined;
+        scopes[key].branchCounts = {};
+        scopes[key].branchFlips = 0;
+        scopes[key].abortedRenders = 0;
+        scopes[key].keySites = {};
+        scopes[key].keyChurn = undefined;
+        scopes[key].propSites = {};
+        scopes[key].unstableProps = undefined;
+        scopes[key].discardedInitializers = undefined;
       });
+      clearCoverage();
+      clearRenderLoops();
+      clearRemounts();
     },
   };
 }
@@ -198,6 +498,103 @@ function callOnRender(
   }
 }
 
+function shouldReportRenderAbort() {
+  return (
+    typeof window?.reactJitter?.onRenderAbort === 'function' &&
+    window.reactJitter.enabled
+  );
+}
+
+function callOnRenderAbort(abort: RenderAbort) {
+  if (shouldReportRenderAbort() && window.reactJitter?.onRenderAbort) {
+    window.reactJitter.onRenderAbort(abort);
+  }
+}
+
+function shouldReportRenderLoop() {
+  return (
+    typeof window?.reactJitter?.onRenderLoop === 'function' &&
+    window.reactJitter.enabled
+  );
+}
+
+function callOnRenderLoop(loop: RenderLoop) {
+  if (shouldReportRenderLoop() && window.reactJitter?.onRenderLoop) {
+    window.reactJitter.onRenderLoop(loop);
+  }
+}
+
+function getOwnerChain(ownerScopeId: string) {
+  const owners: { scopeId: string; name: string }[] = [];
+  let owner: string | undefined = ownerScopeId;
+  while (owner && scopes[owner] && !owners.some((o) => o.scopeId === owner)) {
+    owners.push({ scopeId: owner, name: scopes[owner].name });
+    owner = scopes[owner].owner;
+  }
+  return owners;
+}
+
+function getRenderCause(scope: (typeof scopes)[string]): RenderCause {
+  const ownerRenderCount = scope.owner
+    ? scopes[scope.owner]?.renderCount
+    : undefined;
+  const previousOwnerRenderCount = scope.lastOwnerRenderCount;
+  scope.lastOwnerRenderCount = ownerRenderCount;
+
+  if (scope.renderCount === 1) {
+    return 'mount';
+  }
+  if (scope.hookChanges.length > scope.hookChangesAtRenderStart) {
+    return 'hooks';
+  }
+  if (ownerRenderCount !== previousOwnerRenderCount) {
+    return 'parent';
+  }
+  return 'self';
+}
+
+function isThenable(value: unknown) {
+  return (
+    value !== null &&
+    (typeof value === 'object' || typeof value === 'function') &&
+    typeof (value as { then?: unknown }).then === 'function'
+  );
+}
+
+function getPendingHookIds(scopeId: string) {
+  const prefix = `${scopeId}-`;
+  return [...hookStack.keys()].filter((hookId) => hookId.startsWith(prefix));
+}
+
+function getPendingHookId(scopeId: string) {
+  // The most recently started hook that has not finished yet
+  const pending = getPendingHookIds(scopeId);
+  const hookId = pending[pending.length - 1];
+  return hookId?.slice(scopeId.length + 1);
+}
+
+function getScopePath(scope: Scope) {
+  if (scope.kind !== 'hook') {
+    return [scope.name];
+  }
+
+  // A custom hook scope is created while its caller's h.s/h.e pair is pending
+  const pending = [...hookStack.keys()];
+  const callerHookId = pending[pending.length - 1];
+  const callerScopeId = callerHookId?.slice(0, callerHookId.lastIndexOf('-'));
+  const callerPath = callerScopeId ? scopes[callerScopeId]?.path : undefined;
+  return [...(callerPath ?? []), scope.name];
+}
+
+function getContextName(context: unknown) {
+  const meta = getContextMeta(context);
+  if (meta) {
+    return meta.displayName;
+  }
+  const { displayName } = context as { displayName?: unknown };
+  return typeof displayName === 'string' ? displayName : undefined;
+}
+
 function getScopeCount(scope: Scope) {
   if (!scopeCounter[scope.id]) {
     scopeCounter[scope.id] = 0;
@@ -15,6 +15,16 @@ export const ScopeSchema = z.object({
   file: z.string(),
   line: z.number(),
   offset: z.number(),
+  timing: z.object({ marks: z.boolean() }).optional(),
+  // Custom hook definitions get their own scope inside the calling component
+  kind: z.literal('hook').optional(),
+  // Name of the component this one is declared in
+  nestedIn: z.string().optional(),
+  // Set when ids are derived from names rather than source locations
+  idStrategy: z.literal('structural').optional(),
+  lint: z
+    .array(z.object({ rule: z.string(), name: z.string(), line: z.number() }))
+    .optional(),
 });
 
 export const HookChangeSchema = z.object({
@@ -27,6 +37,10 @@ export const ReactJitterGlobalSchema = z.object({
   enabled: z.boolean().optional(),
   onHookChange: z.function().args(z.any()).returns(z.void()).optional(),
   onRender: z.function().args(z.any()).returns(z.void()).optional(),
+  onRenderAbort: z.function().args(z.any()).returns(z.void()).optional(),
+  getCoverage: z.function().args().returns(z.any()).optional(),
+  onRenderLoop: z.function().args(z.any()).returns(z.void()).optional(),
+  renderLoopThreshold: z.number().optional(),
   clear: z.function().args().returns(z.void()).optional(),
 });
 
@@ -41,8 +55,10 @@ export type HookEndEvent = {
   file: string;
   line: number;
   offset: number;
-  arguments?: string[];
+  arguments?: unknown[];
   isMocked?: boolean;
+  context?: unknown;
+  contextName?: string;
 };
 
 export type HookAddress = Pick<
@@ -60,10 +76,152 @@ export type ReactJitterOptions = {
       scopeId: string;
       hookResults: Record<string, unknown>;
       renderCount: number;
+      branch?: ReturnBranch;
+      branchCounts?: Record<number, number>;
+      branchFlips?: number;
+      renderDuration?: number;
+      abortedRenders?: number;
+      owner?: string;
+      owners?: { scopeId: string; name: string }[];
+      renderCause?: RenderCause;
+      keyChurn?: KeyChurn[];
+      unstableProps?: UnstableProp[];
+      remountReason?: string;
+      discardedInitializers?: DiscardedInitializer[];
     },
   ) => void;
+  onRenderAbort?: (abort: RenderAbort) => void;
+  onRenderLoop?: (loop: RenderLoop) => void;
+  // Setter calls per second at one call site before a render loop is reported
+  renderLoopThreshold?: number;
+};
+
+// `mount`: first render
+// `hooks`: a tracked hook of the component itself changed
+// `parent`: the owner component re-rendered and no tracked hook changed
+// `self`: neither, e.g. a `useState` update inside the component
+export type RenderCause = 'mount' | 'hooks' | 'parent' | 'self';
+
+export type RenderAbort = {
+  scope: Scope & {
+    scopeId: string;
+    renderCount: number;
+    abortedRenders: number;
+  };
+  // `suspended` when a promise was thrown, `errored` for anything else
+  reason: 'suspended' | 'errored';
+  // Id of the hook that was executing when the render was aborted
+  hookId?: string;
+  error: unknown;
 };
 
 export type Scope = z.infer<typeof ScopeSchema>;
 
+export type ReturnBranch = {
+  index: number;
+  line: number;
+  condition?: string;
+};
+
+export type ContextMeta = {
+  id: string;
+  displayName: string;
+  file: string;
+  line: number;
+  offset: number;
+};
+
 export type Comparator = 'deepEqual' | 'circularDeepEqual';
+
+// Registered once per module by the SWC plugin when `coverage` is enabled
+export type RegisteredScope = {
+  name: string;
+  id: string;
+  line: number;
+  hooks: { id: string; hook: string; line: number }[];
+};
+
+export type CoverageReport = {
+  files: {
+    file: string;
+    scopes: (Omit<RegisteredScope, 'hooks'> & {
+      rendered: boolean;
+      hooks: (RegisteredScope['hooks'][number] & { called: boolean })[];
+    })[];
+  }[];
+  scopes: { total: number; rendered: number };
+  hooks: { total: number; called: number };
+};
+
+// A state setter call flagged by the SWC plugin with `detectRenderLoops`
+export type RenderLoopSite = {
+  id: string;
+  setter: string;
+  line: number;
+  offset: number;
+  // `render`: called in the component body, `effect`: in an effect without dependencies
+  kind: 'render' | 'effect';
+};
+
+export type RenderLoop = {
+  scope: Scope & {
+    scopeId: string;
+    renderCount: number;
+  };
+  site: RenderLoopSite;
+  calls: number;
+};
+
+// A `key` attribute inside a `.map` callback, tagged with `trackKeys`
+export type KeySite = {
+  id: string;
+  source: string;
+  line: number;
+};
+
+export type KeyChurn = KeySite & {
+  // Keys that were not rendered by this site in the previous render
+  added: number;
+  // Keys of the previous render that are gone
+  removed: number;
+  total: number;
+};
+
+// A prop passed to a child component element, tagged with `trackProps`
+export type PropSite = {
+  id: string;
+  element: string;
+  prop: string;
+  line: number;
+};
+
+export type UnstableProp = PropSite & {
+  kind: 'function' | 'object' | 'array';
+  renders: number;
+};
+
+// A `useState`/`useReducer` initial value computed on every render, tagged with `countInitializers`
+export type InitializerSite = {
+  id: string;
+  hook: string;
+  source: string;
+  line: number;
+};
+
+export type DiscardedInitializer = InitializerSite & {
+  // Renders after the first that computed the initial value again
+  runs: number;
+};
+
+// Static metadata registered by modules compiled with `metadata: "compact"`
+export type Manifest = {
+  scopes: Record<string, Omit<Scope, 'id'>>;
+  hooks: Record<string, Omit<HookEndEvent, 'id'>>;
+};
+
+// What one module registers: its file once, and each entry without it
+export type ModuleManifest = {
+  file: string;
+  scopes: Record<string, Omit<Scope, 'id' | 'file'>>;
+  hooks: Record<string, Omit<HookEndEvent, 'id' | 'file'>>;
+};
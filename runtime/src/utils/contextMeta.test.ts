@@ -0,0 +1,42 @@
+import { expect, test, describe } from 'vitest';
+import { tagContext, getContextMeta } from './contextMeta';
+import type { ContextMeta } from '../types';
+
+describe('tagContext', () => {
+  const meta: ContextMeta = {
+    id: 'a1b2c3d4',
+    displayName: 'UserContext',
+    file: 'src/contexts/UserContext.tsx',
+    line: 3,
+    offset: 20,
+  };
+
+  test('returns the same context object', () => {
+    const context = {};
+    expect(tagContext(context, meta)).toBe(context);
+  });
+
+  test('sets displayName from the binding name', () => {
+    const context: { displayName?: string } = {};
+    tagContext(context, meta);
+    expect(context.displayName).toBe('UserContext');
+  });
+
+  test('keeps an existing displayName', () => {
+    const context = { displayName: 'CurrentUser' };
+    tagContext(context, meta);
+    expect(context.displayName).toBe('CurrentUser');
+  });
+
+  test('stores metadata by context identity', () => {
+    const context = {};
+    tagContext(context, meta);
+    expect(getContextMeta(context)).toBe(meta);
+    expect(getContextMeta({})).toBeUndefined();
+  });
+
+  test('ignores non-object values', () => {
+    expect(tagContext(null, meta)).toBeNull();
+    expect(getContextMeta(null)).toBeUndefined();
+  });
+});
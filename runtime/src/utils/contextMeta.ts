@@ -0,0 +1,30 @@
+import type { ContextMeta } from '../types';
+
+const contextMetas = new WeakMap<object, ContextMeta>();
+
+/**
+ * Attaches the metadata emitted by the SWC plugin to a context object.
+ * The binding name is used as the `displayName` unless one is already set.
+ */
+export function tagContext<T>(context: T, meta: ContextMeta): T {
+  if (context === null || typeof context !== 'object') {
+    return context;
+  }
+
+  contextMetas.set(context, meta);
+
+  const namedContext = context as { displayName?: string };
+  if (!namedContext.displayName) {
+    namedContext.displayName = meta.displayName;
+  }
+
+  return context;
+}
+
+export function getContextMeta(context: unknown): ContextMeta | undefined {
+  if (context === null || typeof context !== 'object') {
+    return undefined;
+  }
+
+  return contextMetas.get(context);
+}
@@ -0,0 +1,56 @@
+import { beforeEach, describe, expect, test } from 'vitest';
+import {
+  clearCoverage,
+  getCoverage,
+  markHookCalled,
+  markScopeRendered,
+  registerScopes,
+} from './coverage';
+
+describe('coverage', () => {
+  beforeEach(() => {
+    clearCoverage();
+    registerScopes('src/App.tsx', [
+      {
+        name: 'App',
+        id: 'a1',
+        line: 3,
+        hooks: [
+          { id: 'h1', hook: 'useState', line: 4 },
+          { id: 'h2', hook: 'useMemo', line: 5 },
+        ],
+      },
+      { name: 'Footer', id: 'a2', line: 12, hooks: [] },
+    ]);
+  });
+
+  test('reports registered scopes that never rendered', () => {
+    const coverage = getCoverage();
+    expect(coverage.scopes).toEqual({ total: 2, rendered: 0 });
+    expect(coverage.hooks).toEqual({ total: 2, called: 0 });
+    expect(coverage.files[0].scopes[1]).toMatchObject({
+      name: 'Footer',
+      rendered: false,
+    });
+  });
+
+  test('marks rendered scopes and called hooks', () => {
+    markScopeRendered('a1');
+    markHookCalled('h1');
+
+    const coverage = getCoverage();
+    expect(coverage.scopes).toEqual({ total: 2, rendered: 1 });
+    expect(coverage.hooks).toEqual({ total: 2, called: 1 });
+    expect(coverage.files[0].scopes[0].hooks).toEqual([
+      { id: 'h1', hook: 'useState', line: 4, called: true },
+      { id: 'h2', hook: 'useMemo', line: 5, called: false },
+    ]);
+  });
+
+  test('re-registering a file replaces its scopes', () => {
+    registerScopes('src/App.tsx', [
+      { name: 'App', id: 'a1', line: 3, hooks: [] },
+    ]);
+    expect(getCoverage().scopes.total).toBe(1);
+  });
+});
@@ -0,0 +1,55 @@
+import type { CoverageReport, RegisteredScope } from '../types';
+
+const registeredFiles = new Map<string, RegisteredScope[]>();
+const renderedScopes = new Set<string>();
+const calledHooks = new Set<string>();
+
+/**
+ * Records every component scope and hook the SWC plugin instrumented in a
+ * module, so components that never render still show up in coverage.
+ */
+export function registerScopes(file: string, scopes: RegisteredScope[]) {
+  registeredFiles.set(file, scopes);
+}
+
+export function markScopeRendered(id: string) {
+  renderedScopes.add(id);
+}
+
+export function markHookCalled(id: string) {
+  calledHooks.add(id);
+}
+
+export function getCoverage(): CoverageReport {
+  const files = Array.from(registeredFiles, ([file, scopes]) => ({
+    file,
+    scopes: scopes.map((scope) => ({
+      ...scope,
+      rendered: renderedScopes.has(scope.id),
+      hooks: scope.hooks.map((hook) => ({
+        ...hook,
+        called: calledHooks.has(hook.id),
+      })),
+    })),
+  }));
+
+  const scopes = files.flatMap((file) => file.scopes);
+  const hooks = scopes.flatMap((scope) => scope.hooks);
+
+  return {
+    files,
+    scopes: {
+      total: scopes.length,
+      rendered: scopes.filter((scope) => scope.rendered).length,
+    },
+    hooks: {
+      total: hooks.length,
+      called: hooks.filter((hook) => hook.called).length,
+    },
+  };
+}
+
+export function clearCoverage() {
+  renderedScopes.clear();
+  calledHooks.clear();
+}
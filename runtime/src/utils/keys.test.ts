@@ -0,0 +1,50 @@
+import { describe, expect, test } from 'vitest';
+import { getKeyChurn, recordKey } from './keys';
+import type { KeySiteState } from './keys';
+
+const site = { id: 'e0d1721f', source: 'todo.id', line: 3 };
+
+function render(
+  sites: Record<string, KeySiteState>,
+  keys: unknown[],
+  renderCount: number,
+) {
+  for (const key of keys) {
+    recordKey(sites, site, key, renderCount);
+  }
+  return getKeyChurn(sites, renderCount);
+}
+
+describe('getKeyChurn', () => {
+  test('ignores the first render', () => {
+    expect(render({}, [1, 2, 3], 1)).toEqual([]);
+  });
+
+  test('ignores stable and reordered keys', () => {
+    const sites = {};
+    render(sites, [1, 2, 3], 1);
+    expect(render(sites, [3, 1, 2], 2)).toEqual([]);
+  });
+
+  test('ignores lists that only grow or shrink', () => {
+    const sites = {};
+    render(sites, [1, 2], 1);
+    expect(render(sites, [1, 2, 3], 2)).toEqual([]);
+    expect(render(sites, [2], 3)).toEqual([]);
+  });
+
+  test('reports elements replaced by new keys', () => {
+    const sites = {};
+    render(sites, [0.1, 0.2], 1);
+    expect(render(sites, [0.3, 0.4], 2)).toEqual([
+      { ...site, added: 2, removed: 2, total: 2 },
+    ]);
+  });
+
+  test('skips sites that did not render this time', () => {
+    const sites = {};
+    render(sites, [1], 1);
+    render(sites, [2], 2);
+    expect(getKeyChurn(sites, 3)).toEqual([]);
+  });
+});
@@ -0,0 +1,60 @@
+import type { KeyChurn, KeySite } from '../types';
+
+export type KeySiteState = {
+  site: KeySite;
+  renderCount: number;
+  previous: unknown[];
+  current: unknown[];
+};
+
+/**
+ * Records a `key` produced by a `.map` callback during render `renderCount`.
+ * The keys of the previous render are kept for comparison.
+ */
+export function recordKey(
+  sites: Record<string, KeySiteState>,
+  site: KeySite,
+  key: unknown,
+  renderCount: number,
+) {
+  const state = sites[site.id];
+  if (!state) {
+    sites[site.id] = { site, renderCount, previous: [], current: [key] };
+    return;
+  }
+
+  if (state.renderCount !== renderCount) {
+    state.previous = state.current;
+    state.current = [];
+    state.renderCount = renderCount;
+  }
+  state.current.push(key);
+}
+
+/**
+ * Lists the sites whose elements were replaced by elements with new keys in
+ * render `renderCount`, which makes React remount them.
+ */
+export function getKeyChurn(
+  sites: Record<string, KeySiteState>,
+  renderCount: number,
+): KeyChurn[] {
+  const churn: KeyChurn[] = [];
+
+  for (const state of Object.values(sites)) {
+    const { site, previous, current } = state;
+    if (state.renderCount !== renderCount || previous.length === 0) {
+      continue;
+    }
+
+    const previousKeys = new Set(previous);
+    const currentKeys = new Set(current);
+    const added = current.filter((key) => !previousKeys.has(key)).length;
+    const removed = previous.filter((key) => !currentKeys.has(key)).length;
+    if (added > 0 && removed > 0) {
+      churn.push({ ...site, added, removed, total: current.length });
+    }
+  }
+
+  return churn;
+}
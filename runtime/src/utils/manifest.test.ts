@@ -0,0 +1,83 @@
+import { describe, expect, test } from 'vitest';
+import {
+  getManifest,
+  registerManifest,
+  resolveHook,
+  resolveScope,
+} from './manifest';
+
+registerManifest({
+  file: 'src/Profile.tsx',
+  scopes: {
+    '7b913b1c': {
+      name: 'Profile',
+      line: 3,
+      offset: 7,
+    },
+  },
+  hooks: {
+    feb60a92: {
+      hook: 'useUser',
+      line: 5,
+      offset: 15,
+    },
+  },
+});
+
+describe('manifest', () => {
+  test('rehydrates compact scopes', () => {
+    expect(resolveScope({ id: '7b913b1c' })).toEqual({
+      id: '7b913b1c',
+      name: 'Profile',
+      file: 'src/Profile.tsx',
+      line: 3,
+      offset: 7,
+    });
+  });
+
+  test('rehydrates compact hook calls and keeps dynamic fields', () => {
+    expect(resolveHook({ id: 'feb60a92', isMocked: false })).toEqual({
+      id: 'feb60a92',
+      file: 'src/Profile.tsx',
+      hook: 'useUser',
+      line: 5,
+      offset: 15,
+      isMocked: false,
+    });
+  });
+
+  test('resolves a hoisted metadata object to the same report', () => {
+    const hook = Object.freeze({ id: 'feb60a92' });
+    expect(resolveHook(hook)).toBe(resolveHook(hook));
+  });
+
+  test('leaves full metadata untouched', () => {
+    const scope = {
+      id: 'unknown',
+      name: 'App',
+      file: 'src/App.tsx',
+      line: 1,
+      offset: 7,
+    };
+    expect(resolveScope(scope)).toBe(scope);
+  });
+
+  test('merges the manifests of all modules', () => {
+    registerManifest({
+      file: 'src/Footer.tsx',
+      scopes: {
+        a6176a92: {
+          name: 'Footer',
+          line: 1,
+          offset: 7,
+        },
+      },
+      hooks: {},
+    });
+    expect(Object.keys(getManifest().scopes)).toEqual([
+      '7b913b1c',
+      'a6176a92',
+    ]);
+    expect(getManifest().scopes.a6176a92.file).toBe('src/Footer.tsx');
+  });
+});
@@ -0,0 +1,48 @@
+import type { HookEndEvent, Manifest, ModuleManifest, Scope } from '../types';
+
+const manifest: Manifest = { scopes: {}, hooks: {} };
+
+// Metadata objects are hoisted module constants, so each one is resolved once
+const resolvedScopes = new WeakMap<object, Scope>();
+const resolvedHooks = new WeakMap<object, HookEndEvent>();
+
+/**
+ * Records the static metadata of a module compiled with
+ * `metadata: "compact"`, whose scopes and hook calls only carry their id.
+ */
+export function registerManifest({ file, scopes, hooks }: ModuleManifest) {
+  for (const [id, scope] of Object.entries(scopes)) {
+    manifest.scopes[id] = { ...scope, file };
+  }
+  for (const [id, hook] of Object.entries(hooks)) {
+    manifest.hooks[id] = { ...hook, file };
+  }
+}
+
+export function getManifest(): Manifest {
+  return manifest;
+}
+
+export function resolveScope(
+  scope: Pick<Scope, 'id'> & Partial<Scope>,
+): Scope {
+  let resolved = resolvedScopes.get(scope);
+  if (!resolved) {
+    const meta = manifest.scopes[scope.id];
+    resolved = (meta ? { ...meta, ...scope } : scope) as Scope;
+    resolvedScopes.set(scope, resolved);
+  }
+  return resolved;
+}
+
+export function resolveHook(
+  hook: Pick<HookEndEvent, 'id'> & Partial<HookEndEvent>,
+): HookEndEvent {
+  let resolved = resolvedHooks.get(hook);
+  if (!resolved) {
+    const meta = manifest.hooks[hook.id];
+    resolved = (meta ? { ...meta, ...hook } : hook) as HookEndEvent;
+    resolvedHooks.set(hook, resolved);
+  }
+  return resolved;
+}
@@ -0,0 +1,57 @@
+import { describe, expect, test } from 'vitest';
+import { getUnstableProps, recordProp } from './props';
+import type { PropSiteState } from './props';
+
+const site = { id: 'd9bfa914', element: 'Grid', prop: 'onRowClick', line: 3 };
+
+describe('getUnstableProps', () => {
+  test('reports a prop that gets a new function on every render', () => {
+    const sites: Record<string, PropSiteState> = {};
+    for (let render = 1; render <= 3; render++) {
+      recordProp(sites, site, () => render, render);
+    }
+    expect(getUnstableProps(sites, 3)).toEqual([
+      { ...site, kind: 'function', renders: 3 },
+    ]);
+  });
+
+  test('needs at least two renders', () => {
+    const sites: Record<string, PropSiteState> = {};
+    recordProp(sites, site, () => {}, 1);
+    expect(getUnstableProps(sites, 1)).toEqual([]);
+  });
+
+  test('ignores props that kept their reference once', () => {
+    const sites: Record<string, PropSiteState> = {};
+    const handler = () => {};
+    recordProp(sites, site, handler, 1);
+    recordProp(sites, site, handler, 2);
+    recordProp(sites, site, () => {}, 3);
+    expect(getUnstableProps(sites, 3)).toEqual([]);
+  });
+
+  test('ignores primitive values', () => {
+    const sites: Record<string, PropSiteState> = {};
+    recordProp(sites, site, 1, 1);
+    recordProp(sites, site, 2, 2);
+    expect(getUnstableProps(sites, 2)).toEqual([]);
+  });
+
+  test('compares only the first element of a list per render', () => {
+    const sites: Record<string, PropSiteState> = {};
+    const rows = [{}, {}];
+    recordProp(sites, site, rows, 1);
+    recordProp(sites, site, {}, 1);
+    recordProp(sites, site, rows, 2);
+    recordProp(sites, site, {}, 2);
+    expect(getUnstableProps(sites, 2)).toEqual([]);
+  });
+
+  test('describes arrays and objects', () => {
+    const sites: Record<string, PropSiteState> = {};
+    const rowsSite = { ...site, prop: 'rows' };
+    recordProp(sites, rowsSite, [], 1);
+    recordProp(sites, rowsSite, [], 2);
+    expect(getUnstableProps(sites, 2)[0].kind).toBe('array');
+  });
+});
@@ -0,0 +1,73 @@
+import type { PropSite, UnstableProp } from '../types';
+
+export type PropSiteState = {
+  site: PropSite;
+  value: unknown;
+  renderCount: number;
+  renders: number;
+  // Renders that passed a different object or function than the previous one
+  recreated: number;
+};
+
+function isReference(value: unknown) {
+  return (
+    value !== null && (typeof value === 'object' || typeof value === 'function')
+  );
+}
+
+/**
+ * Records the value passed to a child component prop during render
+ * `renderCount`. Only the first element rendered by a site in a render is
+ * compared, so lists are judged by their first row.
+ */
+export function recordProp(
+  sites: Record<string, PropSiteState>,
+  site: PropSite,
+  value: unknown,
+  renderCount: number,
+) {
+  const key = `${site.id}:${site.prop}`;
+  const state = sites[key];
+  if (!state) {
+    sites[key] = { site, value, renderCount, renders: 1, recreated: 0 };
+    return;
+  }
+
+  if (state.renderCount === renderCount) {
+    return;
+  }
+
+  state.renders++;
+  if (isReference(value) && !Object.is(state.value, value)) {
+    state.recreated++;
+  }
+  state.value = value;
+  state.renderCount = renderCount;
+}
+
+/**
+ * Lists the props rendered in render `renderCount` that received a new
+ * object or function in every render after the first.
+ */
+export function getUnstableProps(
+  sites: Record<string, PropSiteState>,
+  renderCount: number,
+): UnstableProp[] {
+  return Object.values(sites)
+    .filter(
+      (state) =>
+        state.renderCount === renderCount &&
+        state.renders > 1 &&
+        state.recreated === state.renders - 1,
+    )
+    .map(({ site, value, renders }) => ({
+      ...site,
+      kind:
+        typeof value === 'function'
+          ? 'function'
+          : Array.isArray(value)
+            ? 'array'
+            : 'object',
+      renders,
+    }));
+}
@@ -0,0 +1,35 @@
+import { beforeEach, describe, expect, test } from 'vitest';
+import { clearRemounts, isRemount, recordRender } from './remounts';
+
+const list = { file: 'src/List.tsx', name: 'List' };
+const item = { id: 'i1', file: 'src/List.tsx', nestedIn: 'List' };
+
+describe('isRemount', () => {
+  beforeEach(() => {
+    clearRemounts();
+  });
+
+  test('does not report instances mounted by one parent render', () => {
+    recordRender(list, 'l1-0', 1);
+    expect(isRemount(item)).toBe(false);
+    expect(isRemount(item)).toBe(false);
+  });
+
+  test('reports instances mounted again by a later parent render', () => {
+    recordRender(list, 'l1-0', 1);
+    isRemount(item);
+    recordRender(list, 'l1-0', 2);
+    expect(isRemount(item)).toBe(true);
+  });
+
+  test('tracks each parent instance separately', () => {
+    recordRender(list, 'l1-0', 1);
+    isRemount(item);
+    recordRender(list, 'l1-1', 1);
+    expect(isRemount(item)).toBe(false);
+  });
+
+  test('ignores components whose parent never rendered', () => {
+    expect(isRemount(item)).toBe(false);
+  });
+});
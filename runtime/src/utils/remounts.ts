@@ -0,0 +1,39 @@
+import type { Scope } from '../types';
+
+type ParentRender = { scopeId: string; renderCount: number };
+
+// Latest render of each component, by file and name
+const lastRenders = new Map<string, ParentRender>();
+// Render of the parent instance that last mounted each nested component
+const nestedMounts = new Map<string, number>();
+
+export function recordRender(
+  scope: Pick<Scope, 'file' | 'name'>,
+  scopeId: string,
+  renderCount: number,
+) {
+  lastRenders.set(`${scope.file}:${scope.name}`, { scopeId, renderCount });
+}
+
+/**
+ * Whether a new instance of a component declared inside `scope.nestedIn`
+ * replaces one mounted by an earlier render of the same parent instance.
+ * Instances mounted by one parent render, such as the items of a list, are
+ * not remounts.
+ */
+export function isRemount(scope: Pick<Scope, 'id' | 'file' | 'nestedIn'>) {
+  const parent = lastRenders.get(`${scope.file}:${scope.nestedIn}`);
+  if (!parent) {
+    return false;
+  }
+
+  const key = `${parent.scopeId}:${scope.id}`;
+  const mountedAt = nestedMounts.get(key);
+  nestedMounts.set(key, parent.renderCount);
+  return mountedAt !== undefined && mountedAt < parent.renderCount;
+}
+
+export function clearRemounts() {
+  lastRenders.clear();
+  nestedMounts.clear();
+}
@@ -0,0 +1,64 @@
+import { beforeEach, describe, expect, test } from 'vitest';
+import {
+  clearRenderLoops,
+  countSetterCall,
+  getRenderLoopMessage,
+} from './renderLoop';
+
+describe('countSetterCall', () => {
+  beforeEach(() => {
+    clearRenderLoops();
+  });
+
+  test('reports once the threshold is exceeded', () => {
+    for (let i = 0; i < 3; i++) {
+      expect(countSetterCall('site', 3, i)).toBeUndefined();
+    }
+    expect(countSetterCall('site', 3, 4)).toBe(4);
+  });
+
+  test('starts over after reporting', () => {
+    for (let i = 0; i < 4; i++) {
+      countSetterCall('site', 3, i);
+    }
+    expect(countSetterCall('site', 3, 5)).toBeUndefined();
+  });
+
+  test('only counts calls within one second', () => {
+    countSetterCall('site', 1, 0);
+    expect(countSetterCall('site', 1, 2000)).toBeUndefined();
+    expect(countSetterCall('site', 1, 2500)).toBe(2);
+  });
+
+  test('counts call sites separately', () => {
+    countSetterCall('a', 1, 0);
+    expect(countSetterCall('b', 1, 0)).toBeUndefined();
+  });
+});
+
+describe('getRenderLoopMessage', () => {
+  test('names the component, setter and location', () => {
+    const message = getRenderLoopMessage({
+      scope: {
+        name: 'Clock',
+        id: 'e4918122',
+        file: 'src/Clock.tsx',
+        line: 3,
+        offset: 7,
+        scopeId: 'e4918122-0',
+        renderCount: 51,
+      },
+      site: {
+        id: 'bd687414',
+        setter: 'setTicks',
+        line: 8,
+        offset: 2,
+        kind: 'render',
+      },
+      calls: 51,
+    });
+    expect(message).toBe(
+      'react-jitter: render loop in Clock (src/Clock.tsx:8): setTicks was called 51 times within a second during render',
+    );
+  });
+});
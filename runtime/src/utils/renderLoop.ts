@@ -0,0 +1,45 @@
+import type { RenderLoop } from '../types';
+
+export const DEFAULT_RENDER_LOOP_THRESHOLD = 50;
+
+// Setter calls are only counted against the threshold within this window
+const WINDOW_MS = 1000;
+
+const siteCalls = new Map<string, { count: number; since: number }>();
+
+/**
+ * Counts a call of a setter flagged by the SWC plugin as a render-loop hazard.
+ * Returns the number of calls once it exceeds `threshold` within one second,
+ * and starts counting again from zero.
+ */
+export function countSetterCall(
+  key: string,
+  threshold: number,
+  time: number,
+): number | undefined {
+  const calls = siteCalls.get(key);
+  if (!calls || time - calls.since > WINDOW_MS) {
+    siteCalls.set(key, { count: 1, since: time });
+    return undefined;
+  }
+
+  calls.count++;
+  if (calls.count <= threshold) {
+    return undefined;
+  }
+
+  siteCalls.delete(key);
+  return calls.count;
+}
+
+export function getRenderLoopMessage({ scope, site, calls }: RenderLoop) {
+  const where = site.kind === 'render' ? 'during render' : 'in an effect';
+  return (
+    `react-jitter: render loop in ${scope.name} (${scope.file}:${site.line}): ` +
+    `${site.setter} was called ${calls} times within a second ${where}`
+  );
+}
+
+export function clearRenderLoops() {
+  siteCalls.clear();
+}
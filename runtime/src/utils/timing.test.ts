@@ -0,0 +1,40 @@
+import { expect, test, describe, afterEach } from 'vitest';
+import { getMeasureName, markRenderStart, measureRender, now } from './timing';
+import type { Scope } from '../types';
+
+describe('timing', () => {
+  const scope: Scope = {
+    name: 'Dashboard',
+    id: '315eebb1',
+    file: 'src/Dashboard.tsx',
+    line: 4,
+    offset: 7,
+    timing: { marks: true },
+  };
+
+  afterEach(() => {
+    performance.clearMeasures();
+    performance.clearMarks();
+  });
+
+  test('now returns increasing timestamps', () => {
+    const start = now();
+    expect(now()).toBeGreaterThanOrEqual(start);
+  });
+
+  test('names measures after the component and its location', () => {
+    expect(getMeasureName(scope)).toBe('⚛ Dashboard (src/Dashboard.tsx:4)');
+  });
+
+  test('measures from the render start mark', () => {
+    markRenderStart('315eebb1-0');
+    measureRender(scope, '315eebb1-0');
+
+    const measures = performance.getEntriesByName(getMeasureName(scope));
+    expect(measures).toHaveLength(1);
+    expect(measures[0].entryType).toBe('measure');
+    expect(
+      performance.getEntriesByName('react-jitter:315eebb1-0:start'),
+    ).toHaveLength(0);
+  });
+});
@@ -0,0 +1,31 @@
+import type { Scope } from '../types';
+
+function hasUserTiming() {
+  return (
+    typeof performance !== 'undefined' &&
+    typeof performance.mark === 'function' &&
+    typeof performance.measure === 'function'
+  );
+}
+
+export function now() {
+  return typeof performance !== 'undefined' ? performance.now() : Date.now();
+}
+
+export function getMeasureName(scope: Scope) {
+  return `⚛ ${scope.name} (${scope.file}:${scope.line})`;
+}
+
+export function markRenderStart(scopeId: string) {
+  if (hasUserTiming()) {
+    performance.mark(`react-jitter:${scopeId}:start`);
+  }
+}
+
+export function measureRender(scope: Scope, scopeId: string) {
+  if (hasUserTiming()) {
+    const startMark = `react-jitter:${scopeId}:start`;
+    performance.measure(getMeasureName(scope), startMark);
+    performance.clearMarks(startMark);
+  }
+}